@@ -0,0 +1,17 @@
+last-about = Show the sessions logged in wtmp, newest first, with when and how they ended.
+last-usage = last [OPTION]...
+last-after-help = The records are read from FILE, or { $default_path } if not specified.
+  Without that file, the current sessions are shown.
+
+# Help messages
+last-help-file = read the records from FILE
+last-help-limit = show at most NUMBER sessions
+last-help-fulltimes = show the full login and logout times
+
+# Error messages
+last-error-cannot-open = cannot open { $file }
+
+# Output
+last-output-begins = { $file } begins { $time }
+
+last-unsupported-openbsd = unsupported command on OpenBSD
@@ -0,0 +1,17 @@
+last-about = Affiche les sessions enregistrées dans wtmp, les plus récentes d'abord, avec quand et comment elles se sont terminées.
+last-usage = last [OPTION]...
+last-after-help = Les enregistrements sont lus depuis FICHIER, ou { $default_path } s'il n'est pas spécifié.
+  Sans ce fichier, les sessions en cours sont affichées.
+
+# Messages d'aide
+last-help-file = lit les enregistrements depuis FICHIER
+last-help-limit = affiche au plus NOMBRE sessions
+last-help-fulltimes = affiche les heures de connexion et de déconnexion complètes
+
+# Messages d'erreur
+last-error-cannot-open = impossible d'ouvrir { $file }
+
+# Sortie
+last-output-begins = { $file } commence { $time }
+
+last-unsupported-openbsd = commande non prise en charge sur OpenBSD
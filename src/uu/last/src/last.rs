@@ -0,0 +1,59 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+// spell-checker:ignore (paths) wtmp fulltimes
+
+use clap::builder::ValueParser;
+use clap::{Arg, ArgAction, Command, value_parser};
+use uucore::format_usage;
+use uucore::translate;
+
+mod platform;
+
+mod options {
+    pub const FILE: &str = "file";
+    pub const LIMIT: &str = "limit";
+    pub const FULL_TIMES: &str = "fulltimes";
+}
+
+/// Where logins and logouts are logged
+const WTMP_FILE: &str = "/var/log/wtmp";
+
+#[uucore::main(no_signals)]
+use platform::uumain;
+
+pub fn uu_app() -> Command {
+    Command::new("last")
+        .version(uucore::crate_version!())
+        .help_template(uucore::localized_help_template("last"))
+        .about(translate!("last-about"))
+        .override_usage(format_usage(&translate!("last-usage")))
+        .infer_long_args(true)
+        .after_help(translate!("last-after-help", "default_path" => WTMP_FILE))
+        .arg(
+            Arg::new(options::FILE)
+                .short('f')
+                .long(options::FILE)
+                .value_name("FILE")
+                .help(translate!("last-help-file"))
+                .value_hint(clap::ValueHint::FilePath)
+                .value_parser(ValueParser::os_string()),
+        )
+        .arg(
+            Arg::new(options::LIMIT)
+                .short('n')
+                .long(options::LIMIT)
+                .value_name("NUMBER")
+                .help(translate!("last-help-limit"))
+                .value_parser(value_parser!(usize)),
+        )
+        .arg(
+            Arg::new(options::FULL_TIMES)
+                .short('F')
+                .long(options::FULL_TIMES)
+                .help(translate!("last-help-fulltimes"))
+                .action(ArgAction::SetTrue),
+        )
+}
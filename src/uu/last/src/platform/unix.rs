@@ -0,0 +1,240 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+// spell-checker:ignore (paths) wtmp
+
+use crate::{WTMP_FILE, options, uu_app};
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use uucore::display::Quotable;
+use uucore::error::{FromIo, UResult};
+use uucore::translate;
+use uucore::utmpx::{RecordType, Utmpx, UtmpxRecord, time};
+
+/// `%a %b %e %H:%M:%S %Y`, e.g. `Fri Jul 14 02:40:00 2017`
+const FULL_TIME_FORMAT: &str =
+    "[weekday repr:short] [month repr:short] [day padding:space] [hour]:[minute]:[second] [year]";
+
+pub fn uumain(args: impl uucore::Args) -> UResult<()> {
+    let matches = uucore::clap_localization::handle_clap_result(uu_app(), args)?;
+
+    let last = Last {
+        limit: matches.get_one::<usize>(options::LIMIT).copied(),
+        full_times: matches.get_flag(options::FULL_TIMES),
+    };
+
+    // Without a wtmp file, the current sessions are all there is to list.
+    let file = match matches.get_one::<OsString>(options::FILE) {
+        Some(file) => Some(Path::new(file)),
+        None => Some(Path::new(WTMP_FILE)).filter(|file| file.exists()),
+    };
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    if let Some(file) = file {
+        // utmpxname() doesn't report files that can't be read.
+        File::open(file).map_err_context(
+            || translate!("last-error-cannot-open", "file" => file.maybe_quote()),
+        )?;
+        let records: Vec<_> = Utmpx::iter_file_records(file).collect();
+        last.print_sessions(&mut out, &records)?;
+        last.print_begins(&mut out, file, &records)?;
+    } else {
+        let records: Vec<_> = Utmpx::iter_all_records().collect();
+        last.print_sessions(&mut out, &records)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// How a session, or the uptime of a boot, ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum End {
+    /// Logged out, or for a boot, shut down or booted again, at this time
+    At(i64),
+    /// Left running when the system booted again at this time without
+    /// shutting down first
+    Crash(i64),
+    /// Left running when the system was shut down at this time
+    Down(i64),
+    /// Left without a logout, though the line has since been logged in again
+    Gone,
+    StillLoggedIn,
+    StillRunning,
+}
+
+/// The sessions and boots in `records`, which are oldest first, with how
+/// each of them ended, newest first
+fn sessions(records: &[UtmpxRecord]) -> Vec<(&UtmpxRecord, End)> {
+    let mut sessions = Vec::new();
+    // The logouts newer than the record at hand, by line
+    let mut logouts: HashMap<String, i64> = HashMap::new();
+    // The lines with a newer login than the record at hand
+    let mut logged_in_again = HashSet::new();
+    // How the sessions still running at the next boot or shutdown ended
+    let mut system_end = None;
+    // The next boot or shutdown, which ends the uptime of a boot
+    let mut next_change = None;
+
+    for record in records.iter().rev() {
+        let time = record.login_time().unix_timestamp();
+        match record.kind() {
+            RecordType::BootTime => {
+                sessions.push((record, next_change.map_or(End::StillRunning, End::At)));
+                system_end = Some(End::Crash(time));
+                next_change = Some(time);
+                logouts.clear();
+                logged_in_again.clear();
+            }
+            #[cfg(not(target_os = "freebsd"))]
+            RecordType::RunLevel if record.user() == "shutdown" => {
+                system_end = Some(End::Down(time));
+                next_change = Some(time);
+                logouts.clear();
+                logged_in_again.clear();
+            }
+            _ if record.is_user_process() => {
+                let line = record.tty_device();
+                let end = match (logouts.remove(&line), system_end) {
+                    (Some(logout), _) => End::At(logout),
+                    (None, Some(end)) => end,
+                    (None, None) if logged_in_again.contains(&line) => End::Gone,
+                    (None, None) => End::StillLoggedIn,
+                };
+                logged_in_again.insert(line);
+                sessions.push((record, end));
+            }
+            // A logout, which leaves the name empty
+            RecordType::DeadProcess | RecordType::UserProcess => {
+                logouts.insert(record.tty_device(), time);
+            }
+            _ => {}
+        }
+    }
+    sessions
+}
+
+/// The length of a session of `seconds`, as `(HH:MM)` or `(D+HH:MM)`
+fn session_length(seconds: i64) -> String {
+    let minutes = seconds.max(0) / 60;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!(" ({days}+{hours:02}:{minutes:02})")
+    } else {
+        format!(" ({hours:02}:{minutes:02})")
+    }
+}
+
+struct Last {
+    limit: Option<usize>,
+    full_times: bool,
+}
+
+impl Last {
+    fn print_sessions(&self, out: &mut impl Write, records: &[UtmpxRecord]) -> UResult<()> {
+        let limit = self.limit.unwrap_or(usize::MAX);
+        for (record, end) in sessions(records).into_iter().take(limit) {
+            self.print_session(out, record, end)?;
+        }
+        Ok(())
+    }
+
+    fn print_session(&self, out: &mut impl Write, record: &UtmpxRecord, end: End) -> UResult<()> {
+        let user = record.user();
+        let line = if record.kind() == RecordType::BootTime {
+            "system boot".to_string()
+        } else {
+            record.tty_device()
+        };
+        let host = record.host();
+        let login = record.login_time();
+        let start = login.unix_timestamp();
+
+        let (until, length) = match end {
+            End::At(time) => (
+                format!("- {}", self.end_time(&login, time)),
+                session_length(time - start),
+            ),
+            End::Crash(time) => ("- crash".to_string(), session_length(time - start)),
+            End::Down(time) => ("- down".to_string(), session_length(time - start)),
+            // The full times leave room for the whole text after the login time
+            End::Gone if self.full_times => ("  gone - no logout".to_string(), String::new()),
+            End::Gone => ("   gone".to_string(), "- no logout".to_string()),
+            End::StillLoggedIn if self.full_times => {
+                ("  still logged in".to_string(), String::new())
+            }
+            End::StillLoggedIn => ("  still".to_string(), "logged in".to_string()),
+            End::StillRunning if self.full_times => ("  still running".to_string(), String::new()),
+            End::StillRunning => ("  still".to_string(), "running".to_string()),
+        };
+        let (time_width, until_width) = if self.full_times { (24, 26) } else { (16, 7) };
+        let entry = format!(
+            "{user:<8.8} {line:<12.12} {host:<16.16} {:<time_width$} {until:<until_width$} {length}",
+            self.login_time(&login),
+        );
+        writeln!(out, "{}", entry.trim_end())?;
+        Ok(())
+    }
+
+    /// A login time, e.g. `Fri Jul 14 02:40`, with seconds and year for `-F`
+    fn login_time(&self, time: &time::OffsetDateTime) -> String {
+        let format = if self.full_times {
+            FULL_TIME_FORMAT
+        } else {
+            "[weekday repr:short] [month repr:short] [day padding:space] [hour]:[minute]"
+        };
+        format_time(time, format)
+    }
+
+    /// The time a session from `login` ended, e.g. `03:06`, or in full for `-F`
+    fn end_time(&self, login: &time::OffsetDateTime, end: i64) -> String {
+        let Ok(end) = time::OffsetDateTime::from_unix_timestamp(end) else {
+            return String::new();
+        };
+        let end = end.to_offset(login.offset());
+        if self.full_times {
+            self.login_time(&end)
+        } else {
+            format_time(&end, "[hour]:[minute]")
+        }
+    }
+
+    /// Print when the records of `file` begin, at its oldest record or else
+    /// when it was last written
+    fn print_begins(
+        &self,
+        out: &mut impl Write,
+        file: &Path,
+        records: &[UtmpxRecord],
+    ) -> UResult<()> {
+        let begins = if let Some(record) = records.first() {
+            record.login_time()
+        } else {
+            let modified = fs::metadata(file).and_then(|md| md.modified())?;
+            let local_offset =
+                time::UtcOffset::current_local_offset().unwrap_or(time::UtcOffset::UTC);
+            time::OffsetDateTime::from(modified).to_offset(local_offset)
+        };
+        let name = file.file_name().unwrap_or(file.as_os_str());
+        writeln!(out)?;
+        writeln!(
+            out,
+            "{}",
+            translate!("last-output-begins",
+                "file" => name.to_string_lossy(),
+                "time" => format_time(&begins, FULL_TIME_FORMAT))
+        )?;
+        Ok(())
+    }
+}
+
+fn format_time(time: &time::OffsetDateTime, format: &str) -> String {
+    let format = time::format_description::parse(format).unwrap();
+    time.format(&format).unwrap_or_default()
+}
@@ -19,7 +19,15 @@ use std::fs::File;
 use std::io;
 use std::io::prelude::*;
 use std::os::unix::fs::MetadataExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// The most of a `.project` or `.plan` file that is printed.
+const MAX_INFO_FILE_SIZE: u64 = 64 * 1024;
+
+/// How long `--lookup` waits for each DNS lookup before showing the host as
+/// recorded, like `who`.
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(3);
 
 fn get_long_usage() -> String {
     format!(
@@ -152,6 +160,16 @@ fn time_string(ut: &UtmpxRecord) -> String {
     ut.login_time().format(&time_format).unwrap()
 }
 
+/// Up to [`MAX_INFO_FILE_SIZE`] bytes of the file at `path`, or `None` if
+/// it can't be read, e.g. because it is missing or unreadable.
+fn read_info_file(path: &Path) -> Option<Vec<u8>> {
+    let mut content = Vec::new();
+    File::open(path)
+        .and_then(|file| file.take(MAX_INFO_FILE_SIZE).read_to_end(&mut content))
+        .ok()?;
+    Some(content)
+}
+
 fn gecos_to_fullname(pw: &Passwd) -> Option<String> {
     let mut gecos = if let Some(gecos) = &pw.user_info {
         gecos.clone()
@@ -185,10 +203,23 @@ impl Pinky {
             last_change = 0;
         }
 
+        // logind knows since when its sessions are idle, including those
+        // without a terminal device, like graphical ones.
+        let last_change = ut
+            .idle_since()
+            .and_then(|since| since.duration_since(UNIX_EPOCH).ok())
+            .map_or(last_change, |since| since.as_secs() as i64);
+
         write!(writer, "{1:<8.0$}", utmpx::UT_NAMESIZE, ut.user())?;
 
         if self.include_fullname {
-            let fullname = if let Ok(pw) = Passwd::locate(ut.user().as_ref()) {
+            // Records from logind know their uid, which is more reliable
+            // than looking the login name up again.
+            let pw = match ut.uid() {
+                Some(uid) => Passwd::locate(uid),
+                None => Passwd::locate(ut.user().as_ref()),
+            };
+            let fullname = if let Ok(pw) = pw {
                 gecos_to_fullname(&pw)
             } else {
                 None
@@ -219,7 +250,7 @@ impl Pinky {
 
         if self.include_where {
             let s: String = if self.do_lookup {
-                ut.canon_host().unwrap_or(ut.host())
+                ut.canon_host_within(RESOLVE_TIMEOUT).unwrap_or(ut.host())
             } else {
                 ut.host()
             };
@@ -264,6 +295,24 @@ impl Pinky {
         Ok(())
     }
 
+    /// Write the `.project` and `.plan` files in the home directory `dir`,
+    /// leaving out those that can't be read like GNU.
+    fn write_info_files(&self, writer: &mut impl Write, dir: &Path) -> io::Result<()> {
+        if self.include_project {
+            if let Some(project) = read_info_file(&dir.join(".project")) {
+                write!(writer, "{} ", translate!("pinky-project-label"))?;
+                writer.write_all(&project)?;
+            }
+        }
+        if self.include_plan {
+            if let Some(plan) = read_info_file(&dir.join(".plan")) {
+                writeln!(writer, "{}:", translate!("pinky-plan-label"))?;
+                writer.write_all(&plan)?;
+            }
+        }
+        Ok(())
+    }
+
     fn write_long(&self, writer: &mut impl Write) -> io::Result<()> {
         for u in &self.names {
             write!(
@@ -285,22 +334,7 @@ impl Pinky {
                     )?;
                     writeln!(writer, "{}  {user_shell}", translate!("pinky-shell-label"))?;
                 }
-                if self.include_project {
-                    let mut p = PathBuf::from(&user_dir);
-                    p.push(".project");
-                    if let Ok(mut reader) = File::open(p) {
-                        write!(writer, "{} ", translate!("pinky-project-label"))?;
-                        io::copy(&mut reader, writer)?;
-                    }
-                }
-                if self.include_plan {
-                    let mut p = PathBuf::from(&user_dir);
-                    p.push(".plan");
-                    if let Ok(mut reader) = File::open(p) {
-                        writeln!(writer, "{}:", translate!("pinky-plan-label"))?;
-                        io::copy(&mut reader, writer)?;
-                    }
-                }
+                self.write_info_files(writer, Path::new(&user_dir))?;
                 writeln!(writer)?;
             } else {
                 writeln!(writer, " ???")?;
@@ -309,3 +343,73 @@ impl Pinky {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn pinky() -> Pinky {
+        Pinky {
+            do_lookup: false,
+            include_idle: true,
+            include_heading: true,
+            include_fullname: true,
+            include_project: true,
+            include_plan: true,
+            include_where: true,
+            include_home_and_shell: true,
+            names: Vec::new(),
+        }
+    }
+
+    fn info_files(pinky: &Pinky, dir: &Path) -> String {
+        let _ = uucore::locale::setup_localization("pinky");
+        let mut out = Vec::new();
+        pinky.write_info_files(&mut out, dir).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_info_files() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(info_files(&pinky(), dir.path()), "");
+
+        fs::write(dir.path().join(".project"), "Rewrite it\n").unwrap();
+        fs::write(dir.path().join(".plan"), "Step 1\nStep 2\n").unwrap();
+        let project = translate!("pinky-project-label");
+        let plan = translate!("pinky-plan-label");
+        assert_eq!(
+            info_files(&pinky(), dir.path()),
+            format!("{project} Rewrite it\n{plan}:\nStep 1\nStep 2\n")
+        );
+
+        let mut without_plan = pinky();
+        without_plan.include_plan = false;
+        assert_eq!(
+            info_files(&without_plan, dir.path()),
+            format!("{project} Rewrite it\n")
+        );
+    }
+
+    #[test]
+    fn test_unreadable_info_files() {
+        // Opening a directory works, reading it doesn't.
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".project")).unwrap();
+        fs::create_dir(dir.path().join(".plan")).unwrap();
+        assert_eq!(info_files(&pinky(), dir.path()), "");
+    }
+
+    #[test]
+    fn test_large_plan_is_cut_short() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".plan"), vec![b'x'; 1024 * 1024]).unwrap();
+        let plan = info_files(&pinky(), dir.path());
+        let label = translate!("pinky-plan-label");
+        assert_eq!(
+            plan,
+            format!("{label}:\n{}", "x".repeat(MAX_INFO_FILE_SIZE as usize))
+        );
+    }
+}
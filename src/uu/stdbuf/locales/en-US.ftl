@@ -24,6 +24,7 @@ stdbuf-error-line-buffering-stdin-meaningless = line buffering stdin is meaningl
 stdbuf-error-invalid-mode = invalid mode {$error}
 stdbuf-error-value-too-large = invalid mode '{$value}': Value too large for defined data type
 stdbuf-error-external-libstdbuf-not-found = External libstdbuf not found at configured path: {$path}
+stdbuf-error-libstdbuf-unavailable = libstdbuf is not available in this build of stdbuf
 stdbuf-error-permission-denied = failed to execute process: Permission denied
 stdbuf-error-no-such-file = failed to execute process: No such file or directory
 stdbuf-error-failed-to-execute = failed to execute process: {$error}
@@ -24,6 +24,7 @@ stdbuf-error-line-buffering-stdin-meaningless = la mise en mémoire tampon par l
 stdbuf-error-invalid-mode = mode invalide {$error}
 stdbuf-error-value-too-large = mode invalide '{$value}' : Valeur trop grande pour le type de données défini
 stdbuf-error-external-libstdbuf-not-found = libstdbuf externe introuvable au chemin configuré : {$path}
+stdbuf-error-libstdbuf-unavailable = libstdbuf n'est pas disponible dans cette version de stdbuf
 stdbuf-error-permission-denied = échec de l'exécution du processus : Permission refusée
 stdbuf-error-no-such-file = échec de l'exécution du processus : Aucun fichier ou répertoire de ce type
 stdbuf-error-failed-to-execute = échec de l'exécution du processus : {$error}
@@ -11,9 +11,13 @@ use clap::{Arg, ArgAction, ArgMatches, Command};
 use std::ffi::OsString;
 #[cfg(unix)]
 use std::os::unix::process::CommandExt;
+#[cfg(not(feature = "feat_external_libstdbuf"))]
+use std::path::Path;
 use std::path::PathBuf;
 use std::process;
+#[cfg(not(feature = "feat_external_libstdbuf"))]
 use tempfile::TempDir;
+#[cfg(not(feature = "feat_external_libstdbuf"))]
 use tempfile::tempdir;
 use thiserror::Error;
 use uucore::error::{UResult, USimpleError, UUsageError};
@@ -45,16 +49,32 @@ const STDBUF_INJECT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/libstdbuf
 #[cfg(all(not(feature = "feat_external_libstdbuf"), target_os = "cygwin"))]
 const STDBUF_INJECT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/libstdbuf.dll"));
 
-enum BufferType {
-    Default,
+/// How a stream is to be buffered, as given by a MODE
+#[derive(Debug, PartialEq, Eq)]
+enum BufferMode {
+    /// `L`
     Line,
-    Size(usize),
+    /// `0`
+    Unbuffered,
+    /// A size, e.g. `4096` or `64K`
+    Sized(u64),
+}
+
+impl BufferMode {
+    /// The value of a `_STDBUF_*` variable, as libstdbuf reads it
+    fn env_value(&self) -> String {
+        match self {
+            Self::Line => "L".to_string(),
+            Self::Unbuffered => "0".to_string(),
+            Self::Sized(size) => size.to_string(),
+        }
+    }
 }
 
 struct ProgramOptions {
-    stdin: BufferType,
-    stdout: BufferType,
-    stderr: BufferType,
+    stdin: Option<BufferMode>,
+    stdout: Option<BufferMode>,
+    stderr: Option<BufferMode>,
 }
 
 impl TryFrom<&ArgMatches> for ProgramOptions {
@@ -84,9 +104,11 @@ fn preload_strings() -> (&'static str, &'static str) {
     ("LD_PRELOAD", "so")
 }
 
+// Set along with DYLD_FORCE_FLAT_NAMESPACE, so that libstdbuf resolves the
+// same stdio streams as the command.
 #[cfg(target_vendor = "apple")]
 fn preload_strings() -> (&'static str, &'static str) {
-    ("DYLD_LIBRARY_PATH", "dylib")
+    ("DYLD_INSERT_LIBRARIES", "dylib")
 }
 
 #[cfg(target_os = "cygwin")]
@@ -94,57 +116,140 @@ fn preload_strings() -> (&'static str, &'static str) {
     ("LD_PRELOAD", "dll")
 }
 
-fn check_option(matches: &ArgMatches, name: &str) -> Result<BufferType, ProgramOptionsError> {
-    match matches.get_one::<String>(name) {
-        Some(value) => match value.as_str() {
-            "L" => {
-                if name == options::INPUT {
-                    Err(ProgramOptionsError::LineBufferingStdinMeaningless)
-                } else {
-                    Ok(BufferType::Line)
-                }
-            }
-            x => parse_size_u64(x).map_or_else(
-                |e| Err(ProgramOptionsError::InvalidMode(e.to_string())),
-                |m| {
-                    Ok(BufferType::Size(m.try_into().map_err(|_| {
-                        ProgramOptionsError::ValueTooLarge(x.to_string())
-                    })?))
-                },
-            ),
-        },
-        None => Ok(BufferType::Default),
+fn check_option(
+    matches: &ArgMatches,
+    name: &str,
+) -> Result<Option<BufferMode>, ProgramOptionsError> {
+    matches
+        .get_one::<String>(name)
+        .map(|value| parse_mode(value, name == options::INPUT))
+        .transpose()
+}
+
+/// Parse the MODE of a stream, which is `L`, `0` or a size with an optional
+/// suffix; `L` is rejected for standard input
+fn parse_mode(value: &str, is_stdin: bool) -> Result<BufferMode, ProgramOptionsError> {
+    if value == "L" {
+        return if is_stdin {
+            Err(ProgramOptionsError::LineBufferingStdinMeaningless)
+        } else {
+            Ok(BufferMode::Line)
+        };
     }
+    let size =
+        parse_size_u64(value).map_err(|e| ProgramOptionsError::InvalidMode(e.to_string()))?;
+    // libstdbuf hands the size to setvbuf(), which takes a size_t.
+    if usize::try_from(size).is_err() {
+        return Err(ProgramOptionsError::ValueTooLarge(value.to_string()));
+    }
+    Ok(match size {
+        0 => BufferMode::Unbuffered,
+        size => BufferMode::Sized(size),
+    })
 }
 
-fn set_command_env(command: &mut process::Command, buffer_name: &str, buffer_type: &BufferType) {
-    match buffer_type {
-        BufferType::Size(m) => {
-            command.env(buffer_name, m.to_string());
-        }
-        BufferType::Line => {
-            command.env(buffer_name, "L");
-        }
-        BufferType::Default => {}
+fn set_command_env(command: &mut process::Command, buffer_name: &str, mode: Option<&BufferMode>) {
+    if let Some(mode) = mode {
+        command.env(buffer_name, mode.env_value());
+    }
+}
+
+/// `libstdbuf` ahead of the libraries the preload variable already lists,
+/// which the command still gets
+fn preload_value(libstdbuf: PathBuf, current: Option<OsString>) -> OsString {
+    let mut value = libstdbuf.into_os_string();
+    if let Some(current) = current.filter(|current| !current.is_empty()) {
+        value.push(":");
+        value.push(current);
     }
+    value
 }
 
 #[cfg(not(feature = "feat_external_libstdbuf"))]
 fn get_preload_env(tmp_dir: &TempDir) -> UResult<(String, PathBuf)> {
-    use std::fs::File;
+    use std::fs::OpenOptions;
     use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    // A build without the library to embed, e.g. from crates.io for a target
+    // that can't preload, would otherwise change no buffering at all.
+    if STDBUF_INJECT.is_empty() {
+        return Err(USimpleError::new(
+            125,
+            translate!("stdbuf-error-libstdbuf-unavailable"),
+        ));
+    }
 
     let (preload, extension) = preload_strings();
     let inject_path = tmp_dir.path().join("libstdbuf").with_extension(extension);
 
-    let mut file = File::create(&inject_path)?;
+    // The directory is private to this invocation, and the library becomes
+    // read-only for the command to map it.
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o500)
+        .open(&inject_path)?;
     file.write_all(STDBUF_INJECT)?;
 
     Ok((preload.to_owned(), inject_path))
 }
 
+/// Remove `dir` once the command, and whatever it starts, has exited.
+///
+/// stdbuf execs the command rather than waiting for it, so a helper process
+/// removes the directory. It waits for end of file on a pipe whose write end
+/// the command inherits: once no process holds it any more, none is left
+/// that could still load libstdbuf from `dir`.
+#[cfg(not(feature = "feat_external_libstdbuf"))]
+fn remove_after_exit(dir: &Path) -> UResult<()> {
+    use nix::sys::wait::waitpid;
+    use nix::unistd::{ForkResult, dup2_stderr, dup2_stdin, dup2_stdout, fork, pipe, setsid};
+    use std::fs::{self, File};
+    use std::io;
+    use std::os::fd::IntoRawFd;
+
+    let cleanup_error = |e| {
+        USimpleError::new(
+            125,
+            format!("failed to start the temp directory cleanup: {e}"),
+        )
+    };
+    let (reader, writer) = pipe().map_err(cleanup_error)?;
+    // SAFETY: stdbuf has no other threads, so the child is free to allocate.
+    match unsafe { fork() }.map_err(cleanup_error)? {
+        ForkResult::Parent { child } => {
+            drop(reader);
+            let _ = waitpid(child, None);
+            // Left open, and across exec, for the command to inherit.
+            let _ = writer.into_raw_fd();
+            Ok(())
+        }
+        ForkResult::Child => {
+            // The helper is forked once more to be reaped by init rather
+            // than left to the command. Neither child ever returns, which
+            // would run the command a second time.
+            if let Ok(ForkResult::Child) = unsafe { fork() } {
+                drop(writer);
+                // Out of the terminal's session, so a ^C for the command
+                // doesn't stop the cleanup, and off its output, so that
+                // nothing reading it waits for the helper.
+                let _ = setsid();
+                if let Ok(null) = File::options().read(true).write(true).open("/dev/null") {
+                    let _ = dup2_stdin(&null);
+                    let _ = dup2_stdout(&null);
+                    let _ = dup2_stderr(&null);
+                }
+                let _ = io::copy(&mut File::from(reader), &mut io::sink());
+                let _ = fs::remove_dir_all(dir);
+            }
+            process::exit(0);
+        }
+    }
+}
+
 #[cfg(feature = "feat_external_libstdbuf")]
-fn get_preload_env(_tmp_dir: &TempDir) -> UResult<(String, PathBuf)> {
+fn get_preload_env() -> UResult<(String, PathBuf)> {
     // Use the directory provided at compile time via LIBSTDBUF_DIR environment variable
     // This will fail to compile if LIBSTDBUF_DIR is not set, which is the desired behavior
     const LIBSTDBUF_DIR: &str = env!("LIBSTDBUF_DIR");
@@ -201,15 +306,27 @@ pub fn uumain(args: impl uucore::Args) -> UResult<()> {
     let mut command = process::Command::new(first_command);
     let command_params: Vec<&OsString> = command_values.collect();
 
+    #[cfg(not(feature = "feat_external_libstdbuf"))]
     let tmp_dir = tempdir()
         .map_err(|e| UUsageError::new(125, format!("failed to create temp directory: {e}")))?;
+    #[cfg(not(feature = "feat_external_libstdbuf"))]
     let (preload_env, libstdbuf) = get_preload_env(&tmp_dir)?;
-    command.env(preload_env, libstdbuf);
-    set_command_env(&mut command, "_STDBUF_I", &options.stdin);
-    set_command_env(&mut command, "_STDBUF_O", &options.stdout);
-    set_command_env(&mut command, "_STDBUF_E", &options.stderr);
+    #[cfg(feature = "feat_external_libstdbuf")]
+    let (preload_env, libstdbuf) = get_preload_env()?;
+    let preload = preload_value(libstdbuf, std::env::var_os(&preload_env));
+    command.env(preload_env, preload);
+    #[cfg(target_vendor = "apple")]
+    command.env("DYLD_FORCE_FLAT_NAMESPACE", "1");
+    set_command_env(&mut command, "_STDBUF_I", options.stdin.as_ref());
+    set_command_env(&mut command, "_STDBUF_O", options.stdout.as_ref());
+    set_command_env(&mut command, "_STDBUF_E", options.stderr.as_ref());
+    // Each argument reaches the command as given, with no shell in between.
     command.args(command_params);
 
+    // If exec fails, dropping tmp_dir removes it right away instead.
+    #[cfg(not(feature = "feat_external_libstdbuf"))]
+    remove_after_exit(tmp_dir.path())?;
+
     // Replace the current process with the target program (no fork) using exec.
     let e = command.exec();
     // exec() only returns if there was an error
@@ -271,3 +388,67 @@ pub fn uu_app() -> Command {
                 .value_parser(clap::value_parser!(OsString)),
         )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mode_line_and_unbuffered() {
+        assert_eq!(parse_mode("L", false).unwrap(), BufferMode::Line);
+        assert_eq!(parse_mode("0", false).unwrap(), BufferMode::Unbuffered);
+        assert_eq!(parse_mode("0", true).unwrap(), BufferMode::Unbuffered);
+        assert_eq!(parse_mode("0K", false).unwrap(), BufferMode::Unbuffered);
+    }
+
+    #[test]
+    fn test_parse_mode_sizes() {
+        for (value, size) in [
+            ("4096", 4096),
+            ("K", 1024),
+            ("1K", 1024),
+            ("64K", 64 * 1024),
+            ("1KB", 1000),
+            ("1KiB", 1024),
+            ("1M", 1024 * 1024),
+            ("1MB", 1000 * 1000),
+            ("1G", 1024 * 1024 * 1024),
+            ("1GB", 1000 * 1000 * 1000),
+        ] {
+            assert_eq!(parse_mode(value, false).unwrap(), BufferMode::Sized(size));
+            assert_eq!(parse_mode(value, true).unwrap(), BufferMode::Sized(size));
+        }
+    }
+
+    #[test]
+    fn test_parse_mode_line_buffering_stdin() {
+        assert!(matches!(
+            parse_mode("L", true),
+            Err(ProgramOptionsError::LineBufferingStdinMeaningless)
+        ));
+    }
+
+    #[test]
+    fn test_parse_mode_invalid() {
+        for value in ["", "l", "1Q", "1024X", "-1", "1.5K", "L1"] {
+            assert!(
+                matches!(
+                    parse_mode(value, false),
+                    Err(ProgramOptionsError::InvalidMode(_))
+                ),
+                "{value}"
+            );
+        }
+        assert!(matches!(
+            parse_mode("1Y", false),
+            Err(ProgramOptionsError::InvalidMode(_) | ProgramOptionsError::ValueTooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn test_env_value() {
+        assert_eq!(BufferMode::Line.env_value(), "L");
+        assert_eq!(BufferMode::Unbuffered.env_value(), "0");
+        assert_eq!(BufferMode::Sized(65536).env_value(), "65536");
+    }
+}
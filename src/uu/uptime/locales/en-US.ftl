@@ -17,15 +17,6 @@ uptime-error-target-is-dir = couldn't get boot time: Is a directory
 uptime-error-target-is-fifo = couldn't get boot time: Illegal seek
 uptime-error-couldnt-get-boot-time = couldn't get boot time
 
-# Output messages
-uptime-output-unknown-uptime = up ???? days ??:??,
-uptime-output-up-text = up
-
-uptime-user-count = { $count ->
-    [one] 1 user
-   *[other] { $count } users
-}
-
 # Error messages
 uptime-lib-error-system-uptime = could not retrieve system uptime
 uptime-lib-error-system-loadavg = could not retrieve system load average
@@ -33,11 +24,6 @@ uptime-lib-error-windows-loadavg = Windows does not have an equivalent to the lo
 uptime-lib-error-boot-time = boot time larger than current time
 
 # Uptime formatting
-uptime-format = { $days ->
-    [0] { $time }
-    [one] { $days } day, { $time }
-   *[other] { $days } days { $time }
-}
 uptime-format-pretty-min = { $min ->
     [one] { $min } minute
    *[other] { $min } minutes
@@ -50,6 +36,3 @@ uptime-format-pretty-day = { $day ->
     [one] { $day } day
    *[other] { $day } days
 }
-
-# Load average formatting
-uptime-lib-format-loadavg = load average: { $avg1 }, { $avg5 }, { $avg15 }
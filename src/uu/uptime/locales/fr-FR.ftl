@@ -17,15 +17,6 @@ uptime-error-target-is-dir = impossible d'obtenir l'heure de démarrage : Est un
 uptime-error-target-is-fifo = impossible d'obtenir l'heure de démarrage : Recherche illégale
 uptime-error-couldnt-get-boot-time = impossible d'obtenir l'heure de démarrage
 
-# Messages de sortie
-uptime-output-unknown-uptime = actif ???? jours ??:??,
-uptime-output-up-text = actif
-
-uptime-user-count = { $count ->
-    [one] 1 utilisateur
-   *[other] { $count } utilisateurs
-}
-
 # Messages d'erreur
 uptime-lib-error-system-uptime = impossible de récupérer la durée de fonctionnement du système
 uptime-lib-error-system-loadavg = impossible de récupérer la charge moyenne du système
@@ -33,11 +24,6 @@ uptime-lib-error-windows-loadavg = Windows n'a pas d'équivalent à la charge mo
 uptime-lib-error-boot-time = heure de démarrage supérieure à l'heure actuelle
 
 # Formatage de la durée de fonctionnement
-uptime-format = { $days ->
-    [0] { $time }
-    [one] { $days } jour, { $time }
-   *[other] { $days } jours { $time }
-}
 uptime-format-pretty-min = { $min ->
     [one] { $min } minute
    *[other] { $min } minutes
@@ -50,6 +36,3 @@ uptime-format-pretty-day = { $day ->
     [one] { $day } jour
    *[other] { $day } jours
 }
-
-# Formatage de la charge moyenne
-uptime-lib-format-loadavg = charge moyenne : { $avg1 }, { $avg5 }, { $avg15 }
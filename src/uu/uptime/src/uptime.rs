@@ -14,18 +14,19 @@ use jiff::{Timestamp, ToSpan};
 use std::ffi::OsString;
 use std::io::{self, Write, stdout};
 use thiserror::Error;
-use uucore::error::{UError, UResult};
+use uucore::error::{UError, UResult, set_exit_code};
 use uucore::format_usage;
 use uucore::libc::time_t;
+use uucore::show_error;
 use uucore::translate;
 use uucore::uptime::{
-    OutputFormat, format_nusers, get_formatted_loadavg, get_formatted_nusers, get_formatted_time,
-    get_formatted_uptime, get_uptime,
+    OutputFormat, format_nusers, get_formatted_header, get_formatted_loadavg, get_formatted_nusers,
+    get_formatted_time, get_formatted_uptime, get_nusers, get_uptime,
 };
 
 #[cfg(unix)]
 #[cfg(not(target_os = "openbsd"))]
-use uucore::utmpx::{BOOT_TIME, USER_PROCESS, Utmpx};
+use uucore::utmpx::{RecordType, Utmpx};
 
 pub mod options {
     pub static SINCE: &str = "since";
@@ -111,8 +112,6 @@ pub fn uu_app() -> Command {
 fn uptime_with_file(file_path: &OsString) -> UResult<()> {
     use std::fs;
     use std::os::unix::fs::FileTypeExt;
-    use uucore::error::set_exit_code;
-    use uucore::show_error;
 
     // Uptime will print loadavg and time to stderr unless we encounter an extra operand.
     let mut non_fatal_error = false;
@@ -202,16 +201,22 @@ fn uptime_with_file(file_path: &OsString) -> UResult<()> {
 }
 
 fn uptime_since() -> UResult<()> {
+    // The boot time as recorded, or else the one the uptime leads back to
     #[cfg(unix)]
     #[cfg(not(target_os = "openbsd"))]
-    let uptime = {
-        let (boot_time, _) = process_utmpx(None);
-        get_uptime(boot_time)?
+    let boot_time = match uucore::utmpx::boot_time() {
+        Ok(boot_time) => Timestamp::try_from(boot_time).ok(),
+        Err(_) => None,
     };
     #[cfg(any(windows, target_os = "openbsd"))]
-    let uptime = get_uptime(None)?;
+    let boot_time = None;
 
-    let since_date = (Timestamp::now() - uptime.seconds()).to_zoned(TimeZone::system());
+    let boot_time = match boot_time {
+        Some(boot_time) => boot_time,
+        None => Timestamp::now() - get_uptime(None)?.seconds(),
+    };
+
+    let since_date = boot_time.to_zoned(TimeZone::system());
     writeln!(stdout(), "{}", since_date.strftime("%Y-%m-%d %H:%M:%S"))?;
 
     Ok(())
@@ -219,10 +224,16 @@ fn uptime_since() -> UResult<()> {
 
 /// Default uptime behaviour i.e. when no file argument is given.
 fn default_uptime() -> UResult<()> {
-    print_time()?;
-    print_uptime(None)?;
-    print_nusers(None)?;
-    print_loadavg()?;
+    let uptime = get_uptime(None);
+    if uptime.is_err() {
+        show_error!("{}", translate!("uptime-error-couldnt-get-boot-time"));
+        set_exit_code(1);
+    }
+    #[cfg(not(target_os = "openbsd"))]
+    let nusers = get_nusers();
+    #[cfg(target_os = "openbsd")]
+    let nusers = get_nusers("/var/run/utmp");
+    writeln!(stdout(), "{}", get_formatted_header(uptime.ok(), nusers))?;
 
     Ok(())
 }
@@ -247,9 +258,9 @@ fn process_utmpx(file: Option<&OsString>) -> (Option<time_t>, usize) {
     };
 
     for line in records {
-        match line.record_type() {
-            x if x == USER_PROCESS => nusers += 1,
-            x if x == BOOT_TIME => {
+        match line.kind() {
+            _ if line.is_user_process() => nusers += 1,
+            RecordType::BootTime => {
                 let dt = line.login_time();
                 if dt.unix_timestamp() > 0 {
                     boot_time = Some(dt.unix_timestamp() as time_t);
@@ -64,12 +64,22 @@ pub fn uumain(args: impl uucore::Args) -> UResult<()> {
     };
     #[cfg(not(target_os = "openbsd"))]
     {
-        let filename = maybe_file.unwrap_or(utmpx::DEFAULT_FILE.as_ref());
-
-        users = Utmpx::iter_all_records_from(filename)
-            .filter(utmpx::UtmpxRecord::is_user_process)
-            .map(|ut| ut.user())
-            .collect::<Vec<_>>();
+        let user_names = |records: utmpx::UtmpxIter| {
+            records
+                .filter(utmpx::UtmpxRecord::is_user_process)
+                .map(|ut| ut.user())
+                .collect::<Vec<_>>()
+        };
+        users = match maybe_file {
+            // An explicit FILE is read as is, even with systemd-logind
+            Some(filename) => user_names(Utmpx::iter_file_records(filename)),
+            // logind has the names without the lookups of full records.
+            // Like the full records, none are listed when logind can't be read.
+            #[cfg(feature = "feat_systemd_logind")]
+            None => uucore::systemd_logind::list_session_users().unwrap_or_default(),
+            #[cfg(not(feature = "feat_systemd_logind"))]
+            None => user_names(Utmpx::iter_all_records()),
+        };
     };
 
     if !users.is_empty() {
@@ -15,21 +15,40 @@ who-help-dead = print dead processes
 who-help-heading = print line of column headings
 who-help-login = print system login processes
 who-help-lookup = attempt to canonicalize hostnames via DNS
+who-help-resolve-timeout = with --lookup, give up on a DNS lookup after DURATION (default 3s) and show the host as recorded
+who-help-host-width = show at most BYTES of each host (default 255, 0 for no limit)
+who-help-idle-threshold = show terminals idle for longer than DURATION (default 24h) as old
+who-help-min-idle = show only sessions idle for longer than DURATION, leaving out those whose idle time is unknown
 who-help-only-hostname-user = only hostname and user associated with stdin
+who-help-tty = only the records on terminal LINE, with or without a leading /dev/
 who-help-process = print active processes spawned by init
 who-help-count = all login names and number of users logged on
+who-help-count-by-host = print the number of user sessions from each host, most first; sessions without a host are counted as local
+who-help-max-users = print nothing, and exit with status 1 if more than N different users are logged on
 who-help-runlevel = print current runlevel
 who-help-runlevel-non-linux = print current runlevel (This is meaningless on non Linux)
 who-help-short = print only name, line, and time (default)
 who-help-time = print last system clock change
 who-help-users = list users logged in
 who-help-mesg = add user's message status as +, - or ?
+who-help-command = show the command line of each session leader
+who-help-deduplicate = show only the most recent session of each user
+who-help-uptime = print the current time, uptime, number of users and load average first
+who-help-numeric-user = print user IDs instead of user names
+who-help-terse = separate columns by a single space instead of aligning them
+who-help-seats = list the seats and the user active on each (needs systemd-logind)
+who-help-best-effort = warn about details of a record that cannot be looked up, such as its host with --lookup, and print the record anyway
+who-help-sort = sort user sessions by FIELD: user, line, time, host or idle; other records keep their place
+who-help-reverse = reverse the order given by --sort
+who-help-watch-interval = keep running, checking every DURATION (e.g. 2s) and printing the records again when they change
+who-help-output = write the selected records in FORMAT instead of text; wtmp: binary utmp records, json: an array of objects with the fields type, user, line, host, login_time, and pid and idle outside of the short format, or only user, line and login_time with -s
+who-help-header-format = override heading labels with comma-separated COLUMN=LABEL pairs; columns: name, line, time, idle, pid, comment, exit, command
+who-help-header-repeat = print the heading again after every N lines, implies -H (0, the default, prints it once)
+who-help-time-style = show login times in STYLE: iso, long-iso, full-iso or +FORMAT, where FORMAT is as in date
 
 # Output messages
-who-user-count = # { $count ->
-    [one] user={ $count }
-   *[other] users={ $count }
-}
+# Not pluralized, to match GNU `who -q`
+who-user-count = # users={ $count }
 
 # Idle time indicators
 who-idle-old =  old
@@ -51,9 +70,22 @@ who-heading-idle = IDLE
 who-heading-pid = PID
 who-heading-comment = COMMENT
 who-heading-exit = EXIT
+who-heading-command = COMMAND
 
 # Error messages
 who-canonicalize-error = failed to canonicalize { $host }
+who-error-invalid-interval = invalid watch interval: { $interval }
+who-error-invalid-resolve-timeout = invalid resolve timeout: { $timeout }
+who-error-invalid-host-width = invalid host width: { $width }
+who-error-seats-unsupported = --seats needs systemd-logind support
+who-error-invalid-header-repeat = invalid header repeat count: { $count }
+who-error-invalid-time-style = invalid time style: { $style }
+who-error-invalid-idle-threshold = invalid idle threshold: { $threshold }
+who-error-invalid-min-idle = invalid minimum idle time: { $duration }
+who-error-invalid-max-users = invalid maximum number of users: { $count }
+who-error-invalid-output = invalid output format: { $format }
+who-warning-config-line = { $path }, line { $line }: expected a known option = "VALUE", ignoring
+who-error-invalid-header-format = invalid column label { $pair }: expected COLUMN=LABEL with a known column
 
 # Platform-specific messages
 who-unsupported-openbsd = unsupported command on OpenBSD
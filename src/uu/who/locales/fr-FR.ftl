@@ -14,21 +14,40 @@ who-help-dead = affiche les processus morts
 who-help-heading = affiche une ligne d'en-têtes de colonnes
 who-help-login = affiche les processus de connexion système
 who-help-lookup = tente de canonicaliser les noms d'hôtes via DNS
+who-help-resolve-timeout = avec --lookup, abandonne une résolution DNS après DURÉE (3s par défaut) et affiche l'hôte tel qu'enregistré
+who-help-host-width = affiche au plus OCTETS de chaque hôte (255 par défaut, 0 pour aucune limite)
+who-help-idle-threshold = affiche comme anciens les terminaux inactifs depuis plus de DURÉE (24h par défaut)
+who-help-min-idle = n'affiche que les sessions inactives depuis plus de DURÉE, sans celles dont la durée d'inactivité est inconnue
 who-help-only-hostname-user = seulement le nom d'hôte et l'utilisateur associés à stdin
+who-help-tty = seulement les enregistrements du terminal LIGNE, avec ou sans /dev/ en tête
 who-help-process = affiche les processus actifs lancés par init
 who-help-count = tous les noms de connexion et le nombre d'utilisateurs connectés
+who-help-count-by-host = affiche le nombre de sessions utilisateur venant de chaque hôte, les plus nombreux d'abord ; les sessions sans hôte sont comptées comme local
+who-help-max-users = n'affiche rien et termine avec le code 1 si plus de N utilisateurs différents sont connectés
 who-help-runlevel = affiche le niveau d'exécution actuel
 who-help-runlevel-non-linux = affiche le niveau d'exécution actuel (Sans signification sur non Linux)
 who-help-short = affiche seulement nom, ligne et heure (par défaut)
 who-help-time = affiche le dernier changement d'horloge système
 who-help-users = liste les utilisateurs connectés
 who-help-mesg = ajoute le statut de message de l'utilisateur comme +, - ou ?
+who-help-command = affiche la ligne de commande de chaque meneur de session
+who-help-deduplicate = affiche seulement la session la plus récente de chaque utilisateur
+who-help-uptime = affiche d'abord l'heure actuelle, la durée de fonctionnement, le nombre d'utilisateurs et la charge moyenne
+who-help-numeric-user = affiche les identifiants des utilisateurs au lieu de leurs noms
+who-help-terse = sépare les colonnes par une seule espace au lieu de les aligner
+who-help-seats = liste les postes et l'utilisateur actif sur chacun (nécessite systemd-logind)
+who-help-best-effort = avertit des détails d'un enregistrement impossibles à obtenir, comme son hôte avec --lookup, et affiche l'enregistrement malgré tout
+who-help-sort = trie les sessions utilisateur selon CHAMP : user, line, time, host ou idle ; les autres enregistrements gardent leur place
+who-help-reverse = inverse l'ordre donné par --sort
+who-help-watch-interval = continue de s'exécuter, vérifie toutes les DURÉE (p. ex. 2s) et réaffiche les enregistrements quand ils changent
+who-help-output = écrit les enregistrements sélectionnés au FORMAT donné au lieu de texte ; wtmp : enregistrements utmp binaires, json : un tableau d'objets avec les champs type, user, line, host, login_time, et pid et idle hors du format court, ou seulement user, line et login_time avec -s
+who-help-header-format = remplace les libellés de l'en-tête par des paires COLONNE=LIBELLÉ séparées par des virgules ; colonnes : name, line, time, idle, pid, comment, exit, command
+who-help-header-repeat = affiche de nouveau l'en-tête toutes les N lignes, implique -H (0, par défaut, l'affiche une fois)
+who-help-time-style = affiche les heures de connexion dans le STYLE : iso, long-iso, full-iso ou +FORMAT, où FORMAT est comme pour date
 
 # Output messages
-who-user-count = # { $count ->
-    [one] utilisateur={ $count }
-   *[other] utilisateurs={ $count }
-}
+# Invariable (sans pluriel), comme GNU `who -q`
+who-user-count = # utilisateurs={ $count }
 
 # Idle time indicators
 who-idle-old = anc.
@@ -50,9 +69,22 @@ who-heading-idle = INACTIF
 who-heading-pid = PID
 who-heading-comment = COMMENTAIRE
 who-heading-exit = SORTIE
+who-heading-command = COMMANDE
 
 # Error messages
 who-canonicalize-error = échec de canonicalisation de { $host }
+who-error-invalid-interval = intervalle de surveillance invalide : { $interval }
+who-error-invalid-resolve-timeout = délai de résolution invalide : { $timeout }
+who-error-invalid-host-width = largeur d'hôte invalide : { $width }
+who-error-seats-unsupported = --seats nécessite la prise en charge de systemd-logind
+who-error-invalid-header-repeat = nombre de répétitions de l'en-tête invalide : { $count }
+who-error-invalid-time-style = style d'heure invalide : { $style }
+who-error-invalid-idle-threshold = seuil d'inactivité invalide : { $threshold }
+who-error-invalid-min-idle = durée d'inactivité minimale invalide : { $duration }
+who-error-invalid-max-users = nombre maximal d'utilisateurs invalide : { $count }
+who-error-invalid-output = format de sortie invalide : { $format }
+who-warning-config-line = { $path }, ligne { $line } : option connue = "VALEUR" attendue, ligne ignorée
+who-error-invalid-header-format = libellé de colonne invalide { $pair } : COLONNE=LIBELLÉ attendu avec une colonne connue
 
 # Platform-specific messages
 who-unsupported-openbsd = commande non prise en charge sur OpenBSD
@@ -0,0 +1,94 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! The column layout of the text output
+//!
+//! The rows are collected before any is written, so that every column can be
+//! as wide as its longest value. A column is never narrower than its GNU
+//! width, which leaves the usual output exactly as GNU lays it out; only a
+//! value too long for its GNU width, such as a long user name, widens the
+//! column for every row instead of pushing the rest of its own row aside.
+
+use std::fmt::Write as _;
+use std::io;
+
+/// A column of the table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    /// The width of the column in GNU's layout
+    pub min_width: usize,
+    /// Whether values are aligned to the right, as process IDs are
+    pub right: bool,
+}
+
+impl Column {
+    pub const fn left(min_width: usize) -> Self {
+        Self {
+            min_width,
+            right: false,
+        }
+    }
+
+    pub const fn right(min_width: usize) -> Self {
+        Self {
+            min_width,
+            right: true,
+        }
+    }
+}
+
+/// Rows waiting for the widths of their columns to be known
+#[derive(Debug, Default)]
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Add a row. All the rows of a table have the same columns: the ones of
+    /// the first row.
+    pub fn push(&mut self, row: impl IntoIterator<Item = (Column, String)>) {
+        let (columns, row): (Vec<_>, Vec<_>) = row.into_iter().unzip();
+        if self.rows.is_empty() {
+            self.columns = columns;
+        }
+        self.rows.push(row);
+    }
+
+    /// Write the rows added so far, separating columns by a single space, and
+    /// start over with an empty table.
+    pub fn write(&mut self, out: &mut impl io::Write) -> io::Result<()> {
+        let widths: Vec<_> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(i))
+                    .map(|value| value.chars().count())
+                    .fold(column.min_width, usize::max)
+            })
+            .collect();
+
+        let mut buf = String::with_capacity(80);
+        for row in self.rows.drain(..) {
+            buf.clear();
+            for (i, (value, column)) in row.iter().zip(&self.columns).enumerate() {
+                if i > 0 {
+                    buf.push(' ');
+                }
+                let width = widths[i];
+                if column.right {
+                    write!(buf, "{value:>width$}").unwrap();
+                } else {
+                    write!(buf, "{value:<width$}").unwrap();
+                }
+            }
+            writeln!(out, "{}", buf.trim_end())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Per-user option defaults, read from `$XDG_CONFIG_HOME/uutils/who.toml`
+//!
+//! The file holds `option = "value"` lines for the long options that take a
+//! value, e.g. `idle-threshold = "1h"`, plus blank lines and `#` comments.
+//! Options given on the command line take precedence. Anything malformed is
+//! reported as a warning and otherwise ignored.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use uucore::display::Quotable;
+use uucore::{show_warning, translate};
+
+use crate::options;
+
+/// The options the config file may set a default for
+const KEYS: [&str; 5] = [
+    options::IDLE_THRESHOLD,
+    options::RESOLVE_TIMEOUT,
+    options::HOST_WIDTH,
+    options::HEADER_FORMAT,
+    options::OUTPUT,
+];
+
+/// Defaults read from the config file, keyed by long option name
+#[derive(Default)]
+pub struct Config {
+    path: PathBuf,
+    values: HashMap<String, String>,
+}
+
+impl Config {
+    /// Read the config file, or return no defaults if there is none
+    pub fn load() -> Self {
+        let Some(path) = config_path() else {
+            return Self::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => Self::parse(path, &content),
+            Err(e) if e.kind() == ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                show_warning!("{}: {e}", path.maybe_quote());
+                Self::default()
+            }
+        }
+    }
+
+    fn parse(path: PathBuf, content: &str) -> Self {
+        let mut values = HashMap::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_line(line) {
+                Some((key, value)) if KEYS.contains(&key) => {
+                    values.insert(key.to_string(), value.to_string());
+                }
+                _ => show_warning!(
+                    "{}",
+                    translate!("who-warning-config-line",
+                        "path" => path.maybe_quote(),
+                        "line" => index + 1)
+                ),
+            }
+        }
+        Self { path, values }
+    }
+
+    /// The configured default of the long option `key`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// Warn that the configured default of an option was rejected
+    pub fn warn_invalid(&self, error: &dyn std::fmt::Display) {
+        show_warning!("{}: {error}", self.path.maybe_quote());
+    }
+}
+
+/// `$XDG_CONFIG_HOME/uutils/who.toml`, with `~/.config` as the default
+/// config directory
+fn config_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(dir.join("uutils").join("who.toml"))
+}
+
+/// Split a `key = "value"` line
+fn parse_line(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once('=')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    (!value.contains(['"', '\\'])).then_some((key.trim(), value))
+}
@@ -3,6 +3,10 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
+#[cfg(not(target_os = "openbsd"))]
+mod columns;
+#[cfg(not(target_os = "openbsd"))]
+mod config;
 #[cfg(not(target_os = "openbsd"))]
 mod unix;
 #[cfg(not(target_os = "openbsd"))]
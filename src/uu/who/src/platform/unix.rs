@@ -3,32 +3,98 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) ttyname hostnames runlevel mesg wtmp statted boottime deadprocs initspawn clockchange curr pidstr exitstr hoststr
+// spell-checker:ignore (ToDO) cmdline ttyname hostnames runlevel mesg wtmp statted boottime deadprocs initspawn clockchange curr pidstr exitstr hoststr
 
+use super::columns::{Column, Table};
+use super::config::Config;
 use crate::options;
 use crate::uu_app;
 
+use clap::ArgMatches;
+
 use uucore::display::Quotable;
-use uucore::error::{FromIo, UResult};
-use uucore::libc::S_IWGRP;
-use uucore::translate;
+use uucore::entries::{Locate, Passwd};
+use uucore::error::{ExitCode, FromIo, UResult, USimpleError};
+use uucore::libc::{S_IWGRP, time_t};
+use uucore::parser::parse_time;
+use uucore::time::FormatSystemTimeFallback;
+use uucore::uptime::{format_header, get_formatted_time, get_loadavg, get_uptime};
+use uucore::{show_warning, translate};
 
-use uucore::utmpx::{self, UtmpxRecord, time};
+use uucore::utmpx::{self, RecordType, UtmpxRecord, time};
 
 use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
-use std::io::{Write as _, stdout};
+use std::fs::File;
+use std::io::ErrorKind;
+use std::io::{self, BufWriter, Write as _, stdout};
+use std::net::Ipv6Addr;
 use std::os::unix::fs::MetadataExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 fn get_long_usage() -> String {
     translate!("who-long-usage", "default_file" => utmpx::DEFAULT_FILE)
 }
 
+/// Print the version and the compiled-in backends as `key=value` lines,
+/// for bug reports.
+fn print_version_full() -> UResult<()> {
+    let yes_no = |enabled: bool| if enabled { "yes" } else { "no" };
+    let mut out = stdout().lock();
+    writeln!(out, "version={}", env!("CARGO_PKG_VERSION"))?;
+    writeln!(out, "target_os={}", std::env::consts::OS)?;
+    writeln!(out, "utmp=yes")?;
+    writeln!(
+        out,
+        "systemd_logind={}",
+        yes_no(cfg!(feature = "feat_systemd_logind"))
+    )?;
+    writeln!(
+        out,
+        "proc={}",
+        yes_no(cfg!(any(target_os = "linux", target_os = "android")))
+    )?;
+    Ok(())
+}
+
+/// Print each seat known to logind and the user active on it, if any
+#[cfg(feature = "feat_systemd_logind")]
+fn print_seats() -> UResult<()> {
+    let mut out = BufWriter::new(stdout().lock());
+    for occupancy in uucore::systemd_logind::read_seat_occupancy()? {
+        let user = occupancy.active_user.unwrap_or_default();
+        let line = format!("{:<12} {user}", occupancy.seat.id);
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Seats only exist for logind, so there is nothing to list without it
+#[cfg(not(feature = "feat_systemd_logind"))]
+fn print_seats() -> UResult<()> {
+    Err(USimpleError::new(
+        1,
+        translate!("who-error-seats-unsupported"),
+    ))
+}
+
 pub fn uumain(args: impl uucore::Args) -> UResult<()> {
     let matches =
         uucore::clap_localization::handle_clap_result(uu_app().after_help(get_long_usage()), args)?;
 
+    if matches.get_flag(options::VERSION_FULL) {
+        return print_version_full();
+    }
+
+    if matches.get_flag(options::SEATS) {
+        return print_seats();
+    }
+
     let files: Vec<String> = matches
         .get_many::<String>(options::FILE)
         .map(|v| v.map(ToString::to_string).collect())
@@ -37,15 +103,66 @@ pub fn uumain(args: impl uucore::Args) -> UResult<()> {
     // If true, attempt to canonicalize hostnames via a DNS lookup.
     let do_lookup = matches.get_flag(options::LOOKUP);
 
+    // If true, failing to look up details of a record is only warned about.
+    let best_effort = matches.get_flag(options::BEST_EFFORT);
+
+    // Defaults for the options taking a value, overridden by the command line.
+    let config = Config::load();
+
+    // How long to wait for each DNS lookup before falling back to the
+    // host as recorded.
+    let resolve_timeout = option_value(
+        &matches,
+        &config,
+        options::RESOLVE_TIMEOUT,
+        parse_resolve_timeout,
+    )?
+    .unwrap_or(DEFAULT_RESOLVE_TIMEOUT);
+
+    // The longest host shown before it is cut short, or 0 for no limit.
+    let host_width = option_value(&matches, &config, options::HOST_WIDTH, parse_host_width)?
+        .unwrap_or(DEFAULT_HOST_WIDTH);
+
+    // How long a terminal can be idle before it is shown as old.
+    let idle_threshold = option_value(
+        &matches,
+        &config,
+        options::IDLE_THRESHOLD,
+        parse_idle_threshold,
+    )?
+    .unwrap_or(DEFAULT_IDLE_THRESHOLD);
+
+    // If set, display only the records idle for longer than this.
+    let min_idle = matches
+        .get_one::<String>(options::MIN_IDLE)
+        .map(|s| parse_min_idle(s))
+        .transpose()?;
+
     // If true, display only a list of usernames and count of
     // the users logged on.
     // Ignored for 'who am i'.
     let short_list = matches.get_flag(options::COUNT);
 
+    // If true, display only the number of user sessions from each host.
+    let count_by_host = matches.get_flag(options::COUNT_BY_HOST);
+
+    // If set, display nothing and fail if more users are logged on.
+    let max_users = matches
+        .get_one::<String>(options::MAX_USERS)
+        .map(|s| parse_max_users(s))
+        .transpose()?;
+
     let all = matches.get_flag(options::ALL);
 
+    // If non-zero, display the heading again after this many lines.
+    let header_repeat = matches
+        .get_one::<String>(options::HEADER_REPEAT)
+        .map(|s| parse_header_repeat(s))
+        .transpose()?
+        .unwrap_or(0);
+
     // If true, display a line at the top describing each field.
-    let include_heading = matches.get_flag(options::HEADING);
+    let include_heading = matches.get_flag(options::HEADING) || header_repeat > 0;
 
     // If true, display a '+' for each user if mesg y, a '-' if mesg n,
     // or a '?' if their tty cannot be statted.
@@ -89,20 +206,111 @@ pub fn uumain(args: impl uucore::Args) -> UResult<()> {
     // If true, display process termination & exit status.
     let include_exit = need_deadprocs;
 
-    // If true, display only name, line, and time fields.
-    let short_output = !include_exit && use_defaults;
+    // If true, display only name, line, and time fields. An explicit -s
+    // overrides the other selection flags, -d included, while the default
+    // output of plain `who` is short as well.
+    let short_output = matches.get_flag(options::SHORT) || use_defaults;
+
+    // If true, JSON objects have only the user, line and time, as asked
+    // for with -s rather than by default.
+    let short_fields = matches.get_flag(options::SHORT);
+
+    // If true, display the command line of each session leader.
+    let include_command = matches.get_flag(options::COMMAND);
+
+    // If true, display only the most recent session of each user.
+    let deduplicate = matches.get_flag(options::DEDUPLICATE);
+
+    // If true, display a `w`-style uptime line before anything else.
+    let include_uptime = matches.get_flag(options::UPTIME);
+
+    // If true, display the uid of each user instead of the name.
+    let numeric_user = matches.get_flag(options::NUMERIC_USER);
+
+    // If true, separate the columns by a single space instead of aligning them.
+    let terse = matches.get_flag(options::TERSE);
+
+    // If set, the order of the user sessions.
+    let sort = matches
+        .get_one::<String>(options::SORT)
+        .map(|field| match field.as_str() {
+            "user" => SortKey::User,
+            "line" => SortKey::Line,
+            "time" => SortKey::Time,
+            "host" => SortKey::Host,
+            _ => SortKey::Idle,
+        });
+    let reverse = matches.get_flag(options::REVERSE);
+
+    // The labels of the heading line.
+    let heading = option_value(&matches, &config, options::HEADER_FORMAT, |s| {
+        Heading::new(Some(s))
+    })?
+    .map_or_else(|| Heading::new(None), Ok)?;
+
+    // How the TIME column shows login times.
+    let time_style = matches
+        .get_one::<String>(options::TIME_STYLE)
+        .map(|s| parse_time_style(s))
+        .transpose()?
+        .unwrap_or(TimeStyle::Default);
+
+    // If set, write the selected records in this format instead of text.
+    // A configured format gives way to the options --output conflicts with.
+    let output = if matches.get_flag(options::COUNT)
+        || matches.get_flag(options::COUNT_BY_HOST)
+        || matches.contains_id(options::MAX_USERS)
+    {
+        None
+    } else {
+        option_value(&matches, &config, options::OUTPUT, parse_output)?
+    };
+
+    // If set, keep running and display the records again whenever they
+    // change, checking at this interval.
+    let watch_interval = matches
+        .get_one::<String>(options::WATCH_INTERVAL)
+        .map(|s| parse_watch_interval(s))
+        .transpose()?;
 
     // If true, display info only for the controlling tty.
     let my_line_only = matches.get_flag(options::ONLY_HOSTNAME_USER) || files.len() == 2;
 
+    // If set, display only the records on this tty.
+    let tty = matches
+        .get_one::<String>(options::TTY)
+        .map(|tty| tty.trim_start_matches("/dev/").to_owned());
+
     let mut who = Who {
         do_lookup,
+        best_effort,
+        resolve_timeout,
+        canon_hosts: RefCell::default(),
+        table: RefCell::default(),
+        host_width,
+        idle_threshold,
+        min_idle,
         short_list,
+        count_by_host,
+        max_users,
         short_output,
+        short_fields,
         include_idle,
         include_heading,
+        header_repeat,
         include_mesg,
         include_exit,
+        include_command,
+        deduplicate,
+        include_uptime,
+        numeric_user,
+        terse,
+        sort,
+        reverse,
+        watch_interval,
+        output,
+        heading,
+        time_style,
         need_boottime,
         need_deadprocs,
         need_login,
@@ -111,6 +319,7 @@ pub fn uumain(args: impl uucore::Args) -> UResult<()> {
         need_runlevel,
         need_users,
         my_line_only,
+        tty,
         args: files,
     };
 
@@ -120,12 +329,36 @@ pub fn uumain(args: impl uucore::Args) -> UResult<()> {
 
 struct Who {
     do_lookup: bool,
+    best_effort: bool,
+    resolve_timeout: Duration,
+    /// Hosts already canonicalized by `--lookup`, by host as recorded
+    canon_hosts: RefCell<HashMap<String, String>>,
+    /// The rows of the text output, written once all of them are known
+    table: RefCell<Table>,
+    host_width: usize,
+    idle_threshold: Duration,
+    min_idle: Option<Duration>,
     short_list: bool,
+    count_by_host: bool,
+    max_users: Option<usize>,
     short_output: bool,
+    short_fields: bool,
     include_idle: bool,
     include_heading: bool,
+    header_repeat: usize,
     include_mesg: bool,
     include_exit: bool,
+    include_command: bool,
+    deduplicate: bool,
+    include_uptime: bool,
+    numeric_user: bool,
+    terse: bool,
+    sort: Option<SortKey>,
+    reverse: bool,
+    watch_interval: Option<Duration>,
+    output: Option<OutputFormat>,
+    heading: Heading,
+    time_style: TimeStyle,
     need_boottime: bool,
     need_deadprocs: bool,
     need_login: bool,
@@ -134,16 +367,18 @@ struct Who {
     need_runlevel: bool,
     need_users: bool,
     my_line_only: bool,
+    tty: Option<String>,
     args: Vec<String>,
 }
 
-fn idle_string<'a>(when: i64, boottime: i64) -> Cow<'a, str> {
+fn idle_string<'a>(when: i64, boottime: i64, threshold: Duration) -> Cow<'a, str> {
     thread_local! {
         static NOW: time::OffsetDateTime = time::OffsetDateTime::now_local().unwrap();
     }
     NOW.with(|n| {
         let now = n.unix_timestamp();
-        if boottime < when && now - 24 * 3600 < when && when <= now {
+        let threshold = i64::try_from(threshold.as_secs()).unwrap_or(i64::MAX);
+        if boottime < when && now.saturating_sub(threshold) < when && when <= now {
             let seconds_idle = now - when;
             if seconds_idle < 60 {
                 "  .  ".into()
@@ -161,100 +396,780 @@ fn idle_string<'a>(when: i64, boottime: i64) -> Cow<'a, str> {
     })
 }
 
-fn time_string(ut: &UtmpxRecord) -> String {
-    let time_format: Vec<time::format_description::FormatItem> = if ["LC_ALL", "LC_TIME", "LANG"]
+/// Whether times are shown in the C locale's format rather than ISO 8601.
+fn c_time_format() -> bool {
+    ["LC_ALL", "LC_TIME", "LANG"]
         .into_iter()
         .find_map(std::env::var_os)
         .as_deref()
         == Some(std::ffi::OsStr::new("C"))
-    {
-        // "%b %e %H:%M"
-        time::format_description::parse("[month repr:short] [day padding:space] [hour]:[minute]")
-            .unwrap()
+}
+
+/// How the TIME column shows login times
+enum TimeStyle {
+    /// GNU's: `%b %e %H:%M` in the C locale, `%Y-%m-%d %H:%M` otherwise
+    Default,
+    /// A `date` format, from `--time-style`
+    Format(String),
+}
+
+impl TimeStyle {
+    /// The width of the times from [`Self::format`], which the TIME column
+    /// is padded to at least.
+    fn width(&self) -> usize {
+        match self {
+            Self::Default if c_time_format() => {
+                // "%b %e %H:%M"
+                3 + 1 + 2 + 1 + 5
+            }
+            Self::Default => {
+                // "%Y-%m-%d %H:%M"
+                10 + 1 + 5
+            }
+            // The times themselves make the column as wide as it needs to be
+            Self::Format(_) => 0,
+        }
+    }
+
+    fn format(&self, ut: &UtmpxRecord) -> String {
+        match self {
+            Self::Default => {
+                let time_format: Vec<time::format_description::FormatItem> = if c_time_format() {
+                    // "%b %e %H:%M"
+                    time::format_description::parse(
+                        "[month repr:short] [day padding:space] [hour]:[minute]",
+                    )
+                    .unwrap()
+                } else {
+                    // "%Y-%m-%d %H:%M"
+                    time::format_description::parse("[year]-[month]-[day] [hour]:[minute]").unwrap()
+                };
+                ut.login_time().format(&time_format).unwrap()
+            }
+            Self::Format(format) => {
+                let mut time = Vec::new();
+                // Out of jiff's range, the time is written as seconds instead.
+                let _ = uucore::time::format_system_time(
+                    &mut time,
+                    SystemTime::from(ut.login_time()),
+                    format,
+                    FormatSystemTimeFallback::Integer,
+                );
+                String::from_utf8_lossy(&time).into_owned()
+            }
+        }
+    }
+}
+
+/// The time style of `--time-style`: `iso`, `long-iso`, `full-iso` or a
+/// `+FORMAT` as in `date` and `ls`
+fn parse_time_style(s: &str) -> UResult<TimeStyle> {
+    let format = match s {
+        "iso" => uucore::time::format::ISO,
+        "long-iso" => uucore::time::format::LONG_ISO,
+        "full-iso" => uucore::time::format::FULL_ISO,
+        _ => s.strip_prefix('+').ok_or_else(|| {
+            USimpleError::new(
+                1,
+                translate!("who-error-invalid-time-style", "style" => s.quote()),
+            )
+        })?,
+    };
+    Ok(TimeStyle::Format(format.to_string()))
+}
+
+/// How long a terminal can be idle before `who -u` shows it as old.
+const DEFAULT_IDLE_THRESHOLD: Duration = Duration::from_secs(24 * 3600);
+
+/// How long `--lookup` waits for each DNS lookup unless `--resolve-timeout` is given.
+const DEFAULT_RESOLVE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Maximum number of bytes shown in the host column unless `--host-width`
+/// is given, so a bogus PTR record can't make a line arbitrarily long.
+const DEFAULT_HOST_WIDTH: usize = 255;
+
+/// Marks a host cut short to fit the host column.
+const HOST_ELLIPSIS: &str = "...";
+
+/// Cut `host` down to at most `width` bytes, ending in [`HOST_ELLIPSIS`]
+/// if anything was removed. A `width` of 0 means no limit.
+fn truncate_host(host: String, width: usize) -> String {
+    if width == 0 || host.len() <= width {
+        return host;
+    }
+    let mut end = width.saturating_sub(HOST_ELLIPSIS.len());
+    while !host.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{HOST_ELLIPSIS}", &host[..end])
+}
+
+/// Maximum number of characters shown in the `--command` column.
+const COMMAND_WIDTH: usize = 64;
+
+/// Read the command line of `pid` from `/proc`, with the NUL separators
+/// turned into spaces and the result truncated to [`COMMAND_WIDTH`].
+fn command_string(pid: i32) -> String {
+    if pid <= 0 {
+        return String::new();
+    }
+    let Ok(raw) = std::fs::read(format!("/proc/{pid}/cmdline")) else {
+        return String::new();
+    };
+    let cmdline = String::from_utf8_lossy(&raw).replace('\0', " ");
+    let cmdline = cmdline.trim_end();
+    if cmdline.chars().count() > COMMAND_WIDTH {
+        let truncated: String = cmdline.chars().take(COMMAND_WIDTH - 3).collect();
+        format!("{truncated}...")
     } else {
-        // "%Y-%m-%d %H:%M"
-        time::format_description::parse("[year]-[month]-[day] [hour]:[minute]").unwrap()
+        cmdline.to_owned()
+    }
+}
+
+/// The uid of the user of `ut`, looked up by name for utmp records.
+///
+/// Falls back to the user name if it can't be resolved.
+fn user_id_string(ut: &UtmpxRecord) -> String {
+    let user = ut.user();
+    ut.uid()
+        .or_else(|| Passwd::locate(user.as_str()).ok().map(|pw| pw.uid))
+        .map_or(user, |uid| uid.to_string())
+}
+
+/// Parse the `--watch-interval` argument, which must be a positive duration.
+fn parse_watch_interval(s: &str) -> UResult<Duration> {
+    match parse_time::from_str(s, true) {
+        Ok(interval) if !interval.is_zero() => Ok(interval),
+        _ => Err(USimpleError::new(
+            1,
+            translate!("who-error-invalid-interval", "interval" => s.quote()),
+        )),
+    }
+}
+
+/// The value of the option `key`: as given on the command line, else as
+/// configured, else `None`
+///
+/// A configured value that doesn't parse is warned about and ignored, while
+/// an invalid command line value is an error.
+fn option_value<T>(
+    matches: &ArgMatches,
+    config: &Config,
+    key: &str,
+    parse: impl Fn(&str) -> UResult<T>,
+) -> UResult<Option<T>> {
+    if let Some(s) = matches.get_one::<String>(key) {
+        return parse(s).map(Some);
+    }
+    Ok(config
+        .get(key)
+        .and_then(|s| parse(s).inspect_err(|e| config.warn_invalid(e)).ok()))
+}
+
+fn parse_idle_threshold(s: &str) -> UResult<Duration> {
+    match parse_time::from_str(s, true) {
+        Ok(threshold) if !threshold.is_zero() => Ok(threshold),
+        _ => Err(USimpleError::new(
+            1,
+            translate!("who-error-invalid-idle-threshold", "threshold" => s.quote()),
+        )),
+    }
+}
+
+fn parse_min_idle(s: &str) -> UResult<Duration> {
+    parse_time::from_str(s, true).map_err(|_| {
+        USimpleError::new(
+            1,
+            translate!("who-error-invalid-min-idle", "duration" => s.quote()),
+        )
+    })
+}
+
+fn parse_resolve_timeout(s: &str) -> UResult<Duration> {
+    match parse_time::from_str(s, true) {
+        Ok(timeout) if !timeout.is_zero() => Ok(timeout),
+        _ => Err(USimpleError::new(
+            1,
+            translate!("who-error-invalid-resolve-timeout", "timeout" => s.quote()),
+        )),
+    }
+}
+
+fn parse_max_users(s: &str) -> UResult<usize> {
+    s.parse().map_err(|_| {
+        USimpleError::new(
+            1,
+            translate!("who-error-invalid-max-users", "count" => s.quote()),
+        )
+    })
+}
+
+fn parse_header_repeat(s: &str) -> UResult<usize> {
+    s.parse().map_err(|_| {
+        USimpleError::new(
+            1,
+            translate!("who-error-invalid-header-repeat", "count" => s.quote()),
+        )
+    })
+}
+
+fn parse_host_width(s: &str) -> UResult<usize> {
+    s.parse().map_err(|_| {
+        USimpleError::new(
+            1,
+            translate!("who-error-invalid-host-width", "width" => s.quote()),
+        )
+    })
+}
+
+fn parse_output(s: &str) -> UResult<OutputFormat> {
+    match s {
+        "wtmp" => Ok(OutputFormat::Wtmp),
+        "json" => Ok(OutputFormat::Json),
+        _ => Err(USimpleError::new(
+            1,
+            translate!("who-error-invalid-output", "format" => s.quote()),
+        )),
+    }
+}
+
+/// Column names accepted by `--header-format`, in display order.
+const HEADING_COLUMNS: [&str; 8] = [
+    "name", "line", "time", "idle", "pid", "comment", "exit", "command",
+];
+
+/// Labels of the `-H` heading, one per column of [`HEADING_COLUMNS`].
+struct Heading([String; 8]);
+
+impl Heading {
+    /// The default labels, with those given in `template` replaced.
+    ///
+    /// The template is a comma-separated list of `COLUMN=LABEL` pairs, e.g.
+    /// `name=USER,line=TTY`.
+    fn new(template: Option<&str>) -> UResult<Self> {
+        let mut labels = HEADING_COLUMNS.map(|column| translate!(&format!("who-heading-{column}")));
+        for pair in template.into_iter().flat_map(|t| t.split(',')) {
+            let index = pair.split_once('=').and_then(|(column, label)| {
+                let index = HEADING_COLUMNS.iter().position(|c| *c == column)?;
+                Some((index, label))
+            });
+            let Some((index, label)) = index else {
+                return Err(USimpleError::new(
+                    1,
+                    translate!("who-error-invalid-header-format", "pair" => pair.quote()),
+                ));
+            };
+            labels[index] = label.to_string();
+        }
+        Ok(Self(labels))
+    }
+}
+
+/// Format of `--output`, when not text.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// Binary utmp records.
+    Wtmp,
+    /// A JSON array with an object per record.
+    Json,
+}
+
+/// `s` as a quoted JSON string.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Field to sort the user sessions by, for `--sort`.
+#[derive(Clone, Copy)]
+enum SortKey {
+    User,
+    Line,
+    Time,
+    Host,
+    /// Most recently active first.
+    Idle,
+}
+
+/// Last access time of the terminal of `ut`, if it can be statted.
+fn tty_atime(ut: &UtmpxRecord) -> Option<i64> {
+    let mut p = PathBuf::from("/dev");
+    p.push(ut.tty_device().as_str());
+    p.metadata().ok().map(|meta| meta.atime())
+}
+
+/// When logind last saw the session of `ut` active, for sessions without a
+/// terminal device.
+fn idle_hint(ut: &UtmpxRecord) -> Option<i64> {
+    ut.idle_since()
+        .and_then(|since| since.duration_since(UNIX_EPOCH).ok())
+        .map(|since| since.as_secs() as i64)
+}
+
+/// When the user of `ut` was last active, as shown in the IDLE column:
+/// logind's idle hint if there is one, else the access time of the terminal.
+fn last_activity(ut: &UtmpxRecord) -> Option<i64> {
+    idle_hint(ut).or_else(|| tty_atime(ut).filter(|&atime| atime != 0))
+}
+
+/// Whether the session of `ut` has been idle for longer than `min_idle`.
+fn idle_longer_than(ut: &UtmpxRecord, min_idle: Duration) -> bool {
+    let Some(last_change) = last_activity(ut) else {
+        return false;
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs() as i64);
+    let min_idle = i64::try_from(min_idle.as_secs()).unwrap_or(i64::MAX);
+    now.saturating_sub(last_change) > min_idle
+}
+
+/// The order in which to print `records`, as indices into it.
+///
+/// Only the user-process records are sorted, among the positions they
+/// already occupy: boot, run-level and other pseudo-records keep their
+/// place. Ties keep the order of the file.
+fn display_order(records: &[UtmpxRecord], sort: Option<SortKey>, reverse: bool) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..records.len()).collect();
+    let Some(key) = sort else {
+        return order;
     };
-    ut.login_time().format(&time_format).unwrap()
+
+    let slots: Vec<usize> = order
+        .iter()
+        .copied()
+        .filter(|&i| records[i].is_user_process())
+        .collect();
+    let mut sorted = slots.clone();
+    sorted.sort_by(|&a, &b| {
+        let (a, b) = (&records[a], &records[b]);
+        let ordering = match key {
+            SortKey::User => a.user().cmp(&b.user()),
+            SortKey::Line => a.tty_device().cmp(&b.tty_device()),
+            SortKey::Time => a.login_time().cmp(&b.login_time()),
+            SortKey::Host => a.host().cmp(&b.host()),
+            // Sessions whose idle time is unknown go last.
+            SortKey::Idle => last_activity(b)
+                .unwrap_or(i64::MIN)
+                .cmp(&last_activity(a).unwrap_or(i64::MIN)),
+        };
+        if reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    for (slot, i) in slots.into_iter().zip(sorted) {
+        order[slot] = i;
+    }
+    order
+}
+
+/// `host` without the `:N` or `:N.M` of an X display, which sessions on
+/// the same host share.
+///
+/// An IPv6 address, with or without a `%zone` or brackets, is kept whole,
+/// even where its last group looks like a display number.
+fn host_without_display(host: &str) -> &str {
+    if let Some(rest) = host.strip_prefix('[') {
+        return rest.find(']').map_or(host, |end| &host[..end + 2]);
+    }
+    let address = host.split_once('%').map_or(host, |(address, _)| address);
+    if address.parse::<Ipv6Addr>().is_ok() {
+        return host;
+    }
+    let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    match host.rsplit_once(':') {
+        Some((name, display))
+            if display
+                .split_once('.')
+                .map_or(is_number(display), |(number, screen)| {
+                    is_number(number) && is_number(screen)
+                }) =>
+        {
+            name
+        }
+        _ => host,
+    }
+}
+
+/// Indices of the most recent user-process record of each user.
+///
+/// When a user has several sessions logged in at the same time, the first
+/// one is kept.
+fn latest_session_per_user(records: &[UtmpxRecord]) -> HashSet<usize> {
+    let mut latest: HashMap<String, usize> = HashMap::new();
+    for (i, ut) in records.iter().enumerate() {
+        if !ut.is_user_process() {
+            continue;
+        }
+        latest
+            .entry(ut.user())
+            .and_modify(|j| {
+                if records[*j].login_time() < ut.login_time() {
+                    *j = i;
+                }
+            })
+            .or_insert(i);
+    }
+    latest.into_values().collect()
+}
+
+/// Fail if `path` exists but can't be read.
+///
+/// A missing file just means there are no records, but an unreadable one is
+/// usually a misconfiguration that would otherwise show up as an empty list.
+fn check_readable(path: &str) -> UResult<()> {
+    match File::open(path) {
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+            Err(e).map_err_context(|| path.maybe_quote().to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Run-level records are only meaningful on Linux.
+#[cfg(target_os = "linux")]
+fn is_runlevel(record_type: RecordType) -> bool {
+    record_type == RecordType::RunLevel
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_runlevel(_record_type: RecordType) -> bool {
+    false
+}
+
+/// Print the names of `users` and how many there are, for `-q`
+fn print_user_list(out: &mut impl io::Write, users: &[String]) -> UResult<()> {
+    writeln!(out, "{}", users.join(" "))?;
+    writeln!(
+        out,
+        "{}",
+        translate!("who-user-count", "count" => users.len())
+    )?;
+    Ok(())
 }
 
 fn current_tty() -> String {
-    rustix::termios::ttyname(std::io::stdin(), Vec::with_capacity(16))
+    rustix::termios::ttyname(io::stdin(), Vec::with_capacity(16))
         .map(|s| s.to_string_lossy().trim_start_matches("/dev/").to_owned())
         .unwrap_or_default()
 }
 
 impl Who {
-    #[allow(clippy::cognitive_complexity)]
     fn exec(&mut self) -> UResult<()> {
-        #[cfg(target_os = "linux")]
-        let run_level_chk = |record: i16| record == utmpx::RUN_LVL;
-        #[cfg(not(target_os = "linux"))]
-        let run_level_chk = |_| false;
-
         let f = if self.args.len() == 1 {
             self.args[0].as_ref()
         } else {
             utmpx::DEFAULT_FILE
         };
+        if !(cfg!(feature = "feat_systemd_logind") && f == utmpx::DEFAULT_FILE) {
+            check_readable(f)?;
+        }
+        // Written in one go rather than a line at a time.
+        let mut out = BufWriter::new(stdout().lock());
+
+        // logind has the names -q lists without the lookups of full records,
+        // as long as no option needs those records to filter them.
+        #[cfg(feature = "feat_systemd_logind")]
+        if self.short_list
+            && !self.filters_records()
+            && self.max_users.is_none()
+            && self.watch_interval.is_none()
+            && f == utmpx::DEFAULT_FILE
+        {
+            let users = uucore::systemd_logind::list_session_users().unwrap_or_default();
+            print_user_list(&mut out, &users)?;
+            out.flush()?;
+            return Ok(());
+        }
+
+        let mut records = self.read_records(f);
+        self.print_records(&mut out, &records)?;
+
+        let Some(interval) = self.watch_interval else {
+            out.flush()?;
+            return Ok(());
+        };
+        loop {
+            out.flush()?;
+            thread::sleep(interval);
+            let current = self.read_records(f);
+            if utmpx::records_changed(&records, &current) {
+                writeln!(out)?;
+                self.print_records(&mut out, &current)?;
+                records = current;
+            }
+        }
+    }
+
+    /// Read the records of `file`. Without a boot record in the system's
+    /// own records, `--boot` falls back to the boot time from elsewhere.
+    /// So do idle times on macOS, whose utmpx may have no boot record: its
+    /// `console` is last accessed at boot, which GNU shows as "old".
+    ///
+    /// A file other than the system's database is read as it is, never
+    /// through logind. Like GNU, one that can't be read lists nothing;
+    /// [`check_readable`] has reported the permission problems.
+    fn read_records(&self, file: &str) -> Vec<UtmpxRecord> {
+        if file != utmpx::DEFAULT_FILE {
+            return utmpx::Utmpx::iter_from_file(Path::new(file))
+                .map(|records| records.map(UtmpxRecord::from).collect())
+                .unwrap_or_default();
+        }
+        let mut records: Vec<_> = utmpx::Utmpx::iter_all_records_from(file).collect();
+        let need_boot_record =
+            self.need_boottime || (cfg!(target_os = "macos") && self.include_idle);
+        if need_boot_record && !records.iter().any(|ut| ut.kind() == RecordType::BootTime) {
+            if let Ok(boot) = utmpx::boot_record() {
+                records.insert(0, boot);
+            }
+        }
+        records
+    }
+
+    fn print_records(&self, out: &mut impl io::Write, records: &[UtmpxRecord]) -> UResult<()> {
+        if let Some(max_users) = self.max_users {
+            // Users with several sessions count once.
+            let users = latest_session_per_user(records).len();
+            return if users <= max_users {
+                Ok(())
+            } else {
+                Err(ExitCode::new(1))
+            };
+        }
         if self.short_list {
-            let users = utmpx::Utmpx::iter_all_records_from(f)
-                .filter(UtmpxRecord::is_user_process)
-                .map(|ut| ut.user())
+            let shown = self.passes_filters(records);
+            let users = records
+                .iter()
+                .zip(shown)
+                .filter(|(ut, shown)| *shown && ut.is_user_process())
+                .map(|(ut, _)| ut.user())
                 .collect::<Vec<_>>();
-            println!("{}", users.join(" "));
-            println!("{}", translate!("who-user-count", "count" => users.len()));
+            return print_user_list(out, &users);
+        }
+        if self.count_by_host {
+            return self.print_host_counts(out, records);
+        }
+
+        let printed = self.print_selected_records(out, records);
+        // Whatever was shown before an error is still written.
+        self.table.borrow_mut().write(out)?;
+        printed
+    }
+
+    /// Whether any of `--deduplicate`, `-m`, `--tty` or `--min-idle` leaves
+    /// records out.
+    #[cfg(feature = "feat_systemd_logind")]
+    fn filters_records(&self) -> bool {
+        self.deduplicate || self.my_line_only || self.tty.is_some() || self.min_idle.is_some()
+    }
+
+    /// Whether each of `records` passes the filters of `--deduplicate`, `-m`,
+    /// `--tty` and `--min-idle`.
+    fn passes_filters(&self, records: &[UtmpxRecord]) -> Vec<bool> {
+        let latest_sessions = if self.deduplicate {
+            latest_session_per_user(records)
+        } else {
+            HashSet::new()
+        };
+        let cur_tty = if self.my_line_only {
+            current_tty()
         } else {
-            let records = utmpx::Utmpx::iter_all_records_from(f);
+            String::new()
+        };
+        records
+            .iter()
+            .enumerate()
+            .map(|(i, ut)| {
+                !(self.deduplicate && ut.is_user_process() && !latest_sessions.contains(&i))
+                    && (!self.my_line_only || cur_tty == ut.tty_device())
+                    && self
+                        .tty
+                        .as_ref()
+                        .is_none_or(|tty| *tty == ut.tty_device().trim_start_matches("/dev/"))
+                    && self.min_idle.is_none_or(|min| idle_longer_than(ut, min))
+            })
+            .collect()
+    }
+
+    /// Print the records the options select, in the chosen format, with the
+    /// rows of the text output added to [`Self::table`].
+    fn print_selected_records(
+        &self,
+        out: &mut impl io::Write,
+        records: &[UtmpxRecord],
+    ) -> UResult<()> {
+        let passes = self.passes_filters(records);
 
+        // Other formats have no room for the text-only lines.
+        if self.output.is_none() {
+            if self.include_uptime {
+                self.print_uptime(out, records)?;
+            }
             if self.include_heading {
-                self.print_heading()?;
+                self.print_heading(out)?;
             }
-            let cur_tty = if self.my_line_only {
-                current_tty()
-            } else {
-                String::new()
-            };
+        }
+        // Like GNU, idle times at or before the last boot seen so far are
+        // reported as "old". This matters for devices such as macOS's
+        // `console`, whose access time dates from the boot itself.
+        let mut boottime = i64::MIN;
+        let mut lines = 0;
+
+        if self.output == Some(OutputFormat::Json) {
+            write!(out, "[")?;
+        }
 
-            for ut in records {
-                if !self.my_line_only || cur_tty == ut.tty_device() {
-                    if self.need_users && ut.is_user_process() {
-                        self.print_user(&ut)?;
-                    } else {
-                        match ut.record_type() {
-                            rt if self.need_runlevel && run_level_chk(rt) => {
-                                if cfg!(target_os = "linux") {
-                                    self.print_runlevel(&ut)?;
-                                }
-                            }
-                            x if x == utmpx::BOOT_TIME && self.need_boottime => {
-                                self.print_boottime(&ut)?;
-                            }
-                            x if x == utmpx::NEW_TIME && self.need_clockchange => {
-                                self.print_clockchange(&ut)?;
-                            }
-                            x if x == utmpx::INIT_PROCESS && self.need_initspawn => {
-                                self.print_initspawn(&ut)?;
-                            }
-                            x if x == utmpx::LOGIN_PROCESS && self.need_login => {
-                                self.print_login(&ut)?;
-                            }
-                            x if x == utmpx::DEAD_PROCESS && self.need_deadprocs => {
-                                self.print_deadprocs(&ut)?;
-                            }
-                            _ => {}
+        for i in display_order(records, self.sort, self.reverse) {
+            let ut = &records[i];
+            if passes[i] && self.is_selected(ut) {
+                match self.output {
+                    Some(OutputFormat::Wtmp) => utmpx::write_record(out, ut)?,
+                    Some(OutputFormat::Json) => {
+                        write!(out, "{}\n  ", if lines == 0 { "" } else { "," })?;
+                        self.print_json_record(out, ut)?;
+                        lines += 1;
+                    }
+                    None => {
+                        if self.header_repeat > 0 && lines > 0 && lines % self.header_repeat == 0 {
+                            self.print_heading(out)?;
                         }
+                        self.print_record(out, ut, boottime)?;
+                        lines += 1;
                     }
                 }
+            }
 
-                if ut.record_type() == utmpx::BOOT_TIME {}
+            if ut.kind() == RecordType::BootTime {
+                boottime = ut.login_time().unix_timestamp();
             }
         }
+
+        if self.output == Some(OutputFormat::Json) {
+            writeln!(out, "{}]", if lines == 0 { "" } else { "\n" })?;
+        }
+        Ok(())
+    }
+
+    /// Print `ut` as a JSON object, for `--output=json`.
+    ///
+    /// Only the options choosing records or columns and `--lookup` apply,
+    /// the ones changing the layout of the text output don't. As in the
+    /// short text format, `pid` and `idle` are left out unless an option
+    /// like `-u` asks for them.
+    fn print_json_record(&self, out: &mut impl io::Write, ut: &UtmpxRecord) -> UResult<()> {
+        let user_process = self.need_users && ut.is_user_process();
+        let record_type = match ut.kind() {
+            _ if user_process => "user",
+            rt if is_runlevel(rt) => "run-level",
+            RecordType::BootTime => "boot",
+            RecordType::NewTime => "clock-change",
+            RecordType::InitProcess => "init",
+            RecordType::LoginProcess => "login",
+            RecordType::DeadProcess => "dead",
+            _ => "other",
+        };
+        let host = if user_process {
+            self.host(ut)?
+        } else {
+            ut.host()
+        };
+        let login_time = ut
+            .login_time()
+            .format(&time::format_description::well_known::Rfc3339)
+            .unwrap_or_default();
+        // Seconds since the user was last active, if known
+        let idle = user_process
+            .then(|| last_activity(ut))
+            .flatten()
+            .map_or_else(
+                || "null".to_string(),
+                |since| {
+                    let now = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |now| now.as_secs() as i64);
+                    now.saturating_sub(since).max(0).to_string()
+                },
+            );
+        let record_type = json_string(record_type);
+        let user = json_string(&ut.user());
+        let line = json_string(&ut.tty_device());
+        let host = json_string(&host);
+        let login_time = json_string(&login_time);
+        let pid = ut.pid().to_string();
+        // The same fields as the columns of the text output, except that -s
+        // keeps only the user, line and time
+        let full = !self.short_fields;
+        let fields = [
+            (full, "type", record_type.as_str()),
+            (true, "user", user.as_str()),
+            (true, "line", line.as_str()),
+            (full, "host", host.as_str()),
+            (true, "login_time", login_time.as_str()),
+            (!self.short_output, "pid", pid.as_str()),
+            (
+                self.include_idle && !self.short_output,
+                "idle",
+                idle.as_str(),
+            ),
+        ];
+        let fields: Vec<_> = fields
+            .into_iter()
+            .filter(|(shown, _, _)| *shown)
+            .map(|(_, key, value)| format!("\"{key}\":{value}"))
+            .collect();
+        write!(out, "{{{}}}", fields.join(","))?;
         Ok(())
     }
 
+    /// Whether the options ask for records of the type of `ut`.
+    fn is_selected(&self, ut: &UtmpxRecord) -> bool {
+        let rt = ut.kind();
+        (self.need_users && ut.is_user_process())
+            || (self.need_runlevel && is_runlevel(rt))
+            || (self.need_boottime && rt == RecordType::BootTime)
+            || (self.need_clockchange && rt == RecordType::NewTime)
+            || (self.need_initspawn && rt == RecordType::InitProcess)
+            || (self.need_login && rt == RecordType::LoginProcess)
+            || (self.need_deadprocs && ut.is_dead_process())
+    }
+
+    /// Print a record selected by [`Self::is_selected`].
+    fn print_record(
+        &self,
+        out: &mut impl io::Write,
+        ut: &UtmpxRecord,
+        boottime: i64,
+    ) -> UResult<()> {
+        if self.need_users && ut.is_user_process() {
+            return self.print_user(out, ut, boottime);
+        }
+        match ut.kind() {
+            rt if is_runlevel(rt) => self.print_runlevel(out, ut),
+            RecordType::BootTime => self.print_boottime(out, ut),
+            RecordType::NewTime => self.print_clockchange(out, ut),
+            RecordType::InitProcess => self.print_initspawn(out, ut),
+            RecordType::LoginProcess => self.print_login(out, ut),
+            _ if ut.is_dead_process() => self.print_deadprocs(out, ut),
+            _ => Ok(()),
+        }
+    }
+
     #[inline]
-    fn print_runlevel(&self, ut: &UtmpxRecord) -> UResult<()> {
+    fn print_runlevel(&self, out: &mut impl io::Write, ut: &UtmpxRecord) -> UResult<()> {
         let last = (ut.pid() / 256) as u8 as char;
         let curr = (ut.pid() % 256) as u8 as char;
         let runlevel_line = translate!("who-runlevel", "level" => curr);
@@ -262,25 +1177,29 @@ impl Who {
             translate!("who-runlevel-last", "last" => (if last == 'N' { 'S' } else { 'N' }));
 
         self.print_line(
+            out,
             "",
             ' ',
             &runlevel_line,
-            &time_string(ut),
+            &self.time_style.format(ut),
             "",
             "",
             if last.is_control() { "" } else { &comment },
             "",
+            "",
         )?;
         Ok(())
     }
 
     #[inline]
-    fn print_clockchange(&self, ut: &UtmpxRecord) -> UResult<()> {
+    fn print_clockchange(&self, out: &mut impl io::Write, ut: &UtmpxRecord) -> UResult<()> {
         self.print_line(
+            out,
             "",
             ' ',
             &translate!("who-clock-change"),
-            &time_string(ut),
+            &self.time_style.format(ut),
+            "",
             "",
             "",
             "",
@@ -290,65 +1209,73 @@ impl Who {
     }
 
     #[inline]
-    fn print_login(&self, ut: &UtmpxRecord) -> UResult<()> {
+    fn print_login(&self, out: &mut impl io::Write, ut: &UtmpxRecord) -> UResult<()> {
         let comment = translate!("who-login-id", "id" => ut.terminal_suffix());
         let pidstr = format!("{}", ut.pid());
         self.print_line(
+            out,
             &translate!("who-login"),
             ' ',
             &ut.tty_device(),
-            &time_string(ut),
+            &self.time_style.format(ut),
             "",
             &pidstr,
             &comment,
             "",
+            "",
         )?;
         Ok(())
     }
 
     #[inline]
-    fn print_deadprocs(&self, ut: &UtmpxRecord) -> UResult<()> {
+    fn print_deadprocs(&self, out: &mut impl io::Write, ut: &UtmpxRecord) -> UResult<()> {
         let comment = translate!("who-login-id", "id" => ut.terminal_suffix());
         let pidstr = format!("{}", ut.pid());
         let e = ut.exit_status();
         let exitstr = translate!("who-dead-exit-status", "term" => e.0, "exit" => e.1);
         self.print_line(
+            out,
             "",
             ' ',
             &ut.tty_device(),
-            &time_string(ut),
+            &self.time_style.format(ut),
             "",
             &pidstr,
             &comment,
             &exitstr,
+            "",
         )?;
         Ok(())
     }
 
     #[inline]
-    fn print_initspawn(&self, ut: &UtmpxRecord) -> UResult<()> {
+    fn print_initspawn(&self, out: &mut impl io::Write, ut: &UtmpxRecord) -> UResult<()> {
         let comment = translate!("who-login-id", "id" => ut.terminal_suffix());
         let pidstr = format!("{}", ut.pid());
         self.print_line(
+            out,
             "",
             ' ',
             &ut.tty_device(),
-            &time_string(ut),
+            &self.time_style.format(ut),
             "",
             &pidstr,
             &comment,
             "",
+            "",
         )?;
         Ok(())
     }
 
     #[inline]
-    fn print_boottime(&self, ut: &UtmpxRecord) -> UResult<()> {
+    fn print_boottime(&self, out: &mut impl io::Write, ut: &UtmpxRecord) -> UResult<()> {
         self.print_line(
+            out,
             "",
             ' ',
             &translate!("who-system-boot"),
-            &time_string(ut),
+            &self.time_style.format(ut),
+            "",
             "",
             "",
             "",
@@ -357,7 +1284,7 @@ impl Who {
         Ok(())
     }
 
-    fn print_user(&self, ut: &UtmpxRecord) -> UResult<()> {
+    fn print_user(&self, out: &mut impl io::Write, ut: &UtmpxRecord, boottime: i64) -> UResult<()> {
         let mut p = PathBuf::from("/dev");
         p.push(ut.tty_device().as_str());
         let mesg;
@@ -378,40 +1305,107 @@ impl Who {
             last_change = 0;
         }
 
+        // Sessions without a terminal device, like graphical ones, may still
+        // have an idle time from logind.
+        let last_change = if last_change == 0 {
+            idle_hint(ut).unwrap_or(0)
+        } else {
+            last_change
+        };
         let idle = if last_change == 0 {
             "  ?".into()
         } else {
-            idle_string(last_change, 0)
+            idle_string(last_change, boottime, self.idle_threshold)
         };
 
-        let s = if self.do_lookup {
-            ut.canon_host().map_err_context(|| {
-                let host = ut.host();
-                translate!("who-canonicalize-error", "host" => host.split(':').next().unwrap_or(&host).quote())
-                .to_string()
-            })?
+        let s = truncate_host(self.host(ut)?, self.host_width);
+        let hoststr = if s.is_empty() { s } else { format!("({s})") };
+
+        let command = if self.include_command {
+            command_string(ut.pid())
         } else {
-            ut.host()
+            String::new()
+        };
+
+        let user = if self.numeric_user {
+            user_id_string(ut)
+        } else {
+            ut.user()
         };
-        let hoststr = if s.is_empty() { s } else { format!("({s})") };
 
         self.print_line(
-            ut.user().as_ref(),
+            out,
+            user.as_ref(),
             mesg,
             ut.tty_device().as_ref(),
-            time_string(ut).as_str(),
+            self.time_style.format(ut).as_str(),
             idle.as_ref(),
             format!("{}", ut.pid()).as_str(),
             hoststr.as_str(),
             "",
+            command.as_str(),
         )?;
 
         Ok(())
     }
 
+    /// The host of `ut`, canonicalized with `--lookup`. Each host is only
+    /// looked up once.
+    fn host(&self, ut: &UtmpxRecord) -> UResult<String> {
+        let host = ut.host();
+        if !self.do_lookup {
+            return Ok(host);
+        }
+        if let Some(canon) = self.canon_hosts.borrow().get(&host) {
+            return Ok(canon.clone());
+        }
+        let canon_host = ut.canon_host_within(self.resolve_timeout).map_err_context(|| {
+            translate!("who-canonicalize-error", "host" => host.split(':').next().unwrap_or(&host).quote())
+            .to_string()
+        });
+        let canon = match canon_host {
+            Ok(canon) => canon,
+            // Keep the line, with the host as recorded.
+            Err(e) if self.best_effort => {
+                show_warning!("{e}");
+                host.clone()
+            }
+            Err(e) => return Err(e),
+        };
+        self.canon_hosts.borrow_mut().insert(host, canon.clone());
+        Ok(canon)
+    }
+
+    /// Print `host: N` for the user sessions from each host, most first, of
+    /// those [`Self::passes_filters`] keeps.
+    /// Sessions without a remote host, such as those on a seat or a local
+    /// X display, count as `local`.
+    fn print_host_counts(&self, out: &mut impl io::Write, records: &[UtmpxRecord]) -> UResult<()> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        let shown = self.passes_filters(records);
+        for (ut, _) in records
+            .iter()
+            .zip(shown)
+            .filter(|(ut, shown)| *shown && ut.is_user_process())
+        {
+            let host = self.host(ut)?;
+            let name = host_without_display(&host);
+            let name = if name.is_empty() { "local" } else { name };
+            *counts.entry(name.to_string()).or_default() += 1;
+        }
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by(|(a, m), (b, n)| n.cmp(m).then_with(|| a.cmp(b)));
+        for (host, count) in counts {
+            writeln!(out, "{host}: {count}")?;
+        }
+        Ok(())
+    }
+
+    /// Add a row to the text output, or print it right away with `--terse`.
     #[allow(clippy::too_many_arguments)]
     fn print_line(
         &self,
+        out: &mut impl io::Write,
         user: &str,
         state: char,
         line: &str,
@@ -420,44 +1414,109 @@ impl Who {
         pid: &str,
         comment: &str,
         exit: &str,
+        command: &str,
     ) -> UResult<()> {
-        let mut buf = String::with_capacity(64);
-        let msg = vec![' ', state].into_iter().collect::<String>();
-
-        write!(buf, "{user:<8}").unwrap();
-        if self.include_mesg {
-            buf.push_str(&msg);
+        if self.terse {
+            return self.print_terse_line(
+                out, user, state, line, time, idle, pid, comment, exit, command,
+            );
         }
-        write!(buf, " {line:<12}").unwrap();
-        // "%b %e %H:%M" (LC_ALL=C)
-        let time_size = 3 + 2 + 2 + 1 + 2;
-        write!(buf, " {time:<time_size$}").unwrap();
-
-        if !self.short_output {
-            if self.include_idle {
-                write!(buf, " {idle:<6}").unwrap();
-            }
-            write!(buf, " {pid:>10}").unwrap();
-        }
-        write!(buf, " {comment:<8}").unwrap();
-        if self.include_exit {
-            write!(buf, " {exit:<12}").unwrap();
-        }
-        writeln!(stdout(), "{}", buf.trim_end())?;
+
+        let show_pid = !self.short_output;
+        let columns = [
+            (true, Column::left(8), user.to_string()),
+            (self.include_mesg, Column::left(1), state.to_string()),
+            (true, Column::left(12), line.to_string()),
+            (
+                true,
+                Column::left(self.time_style.width()),
+                time.to_string(),
+            ),
+            (
+                show_pid && self.include_idle,
+                Column::left(6),
+                idle.to_string(),
+            ),
+            (show_pid, Column::right(10), pid.to_string()),
+            (true, Column::left(8), comment.to_string()),
+            (self.include_exit, Column::left(12), exit.to_string()),
+            (self.include_command, Column::left(0), command.to_string()),
+        ];
+        self.table.borrow_mut().push(
+            columns
+                .into_iter()
+                .filter(|(shown, _, _)| *shown)
+                .map(|(_, column, value)| (column, value)),
+        );
+        Ok(())
+    }
+
+    /// Like [`Self::print_line`], with the non-empty columns separated by a
+    /// single space.
+    #[allow(clippy::too_many_arguments)]
+    fn print_terse_line(
+        &self,
+        out: &mut impl io::Write,
+        user: &str,
+        state: char,
+        line: &str,
+        time: &str,
+        idle: &str,
+        pid: &str,
+        comment: &str,
+        exit: &str,
+        command: &str,
+    ) -> UResult<()> {
+        let state = state.to_string();
+        let columns = [
+            (true, user),
+            (self.include_mesg, state.as_str()),
+            (true, line),
+            (true, time),
+            (self.include_idle && !self.short_output, idle),
+            (!self.short_output, pid),
+            (true, comment),
+            (self.include_exit, exit),
+            (self.include_command, command),
+        ];
+        let fields: Vec<_> = columns
+            .into_iter()
+            .filter(|(shown, _)| *shown)
+            .map(|(_, field)| field.trim())
+            .filter(|field| !field.is_empty())
+            .collect();
+        writeln!(out, "{}", fields.join(" "))?;
+        Ok(())
+    }
+
+    /// Print the current time, uptime, number of user sessions and load
+    /// average, in the layout of `uptime` and `w`.
+    fn print_uptime(&self, out: &mut impl io::Write, records: &[UtmpxRecord]) -> UResult<()> {
+        let boot_time = records
+            .iter()
+            .filter(|ut| ut.kind() == RecordType::BootTime)
+            .map(|ut| ut.login_time().unix_timestamp())
+            .rfind(|&ts| ts > 0);
+        let nusers = records.iter().filter(|ut| ut.is_user_process()).count();
+
+        writeln!(
+            out,
+            "{}",
+            format_header(
+                &get_formatted_time(),
+                get_uptime(boot_time.map(|ts| ts as time_t)).ok(),
+                nusers,
+                get_loadavg().ok(),
+            )
+        )?;
         Ok(())
     }
 
     #[inline]
-    fn print_heading(&self) -> UResult<()> {
+    fn print_heading(&self, out: &mut impl io::Write) -> UResult<()> {
+        let [name, line, time, idle, pid, comment, exit, command] = &self.heading.0;
         self.print_line(
-            &translate!("who-heading-name"),
-            ' ',
-            &translate!("who-heading-line"),
-            &translate!("who-heading-time"),
-            &translate!("who-heading-idle"),
-            &translate!("who-heading-pid"),
-            &translate!("who-heading-comment"),
-            &translate!("who-heading-exit"),
+            out, name, ' ', line, time, idle, pid, comment, exit, command,
         )?;
         Ok(())
     }
@@ -18,14 +18,36 @@ mod options {
     pub const HEADING: &str = "heading";
     pub const LOGIN: &str = "login";
     pub const LOOKUP: &str = "lookup";
+    pub const IDLE_THRESHOLD: &str = "idle-threshold";
+    pub const MIN_IDLE: &str = "min-idle";
+    pub const RESOLVE_TIMEOUT: &str = "resolve-timeout";
+    pub const HOST_WIDTH: &str = "host-width";
     pub const ONLY_HOSTNAME_USER: &str = "only_hostname_user";
+    pub const TTY: &str = "tty";
     pub const PROCESS: &str = "process";
     pub const COUNT: &str = "count";
+    pub const COUNT_BY_HOST: &str = "count-by-host";
+    pub const MAX_USERS: &str = "max-users";
     pub const RUNLEVEL: &str = "runlevel";
     pub const SHORT: &str = "short";
     pub const TIME: &str = "time";
     pub const USERS: &str = "users";
     pub const MESG: &str = "mesg"; // aliases: --message, --writable
+    pub const COMMAND: &str = "command";
+    pub const DEDUPLICATE: &str = "deduplicate";
+    pub const UPTIME: &str = "uptime";
+    pub const NUMERIC_USER: &str = "numeric-user";
+    pub const TERSE: &str = "terse"; // alias: --no-pad
+    pub const SEATS: &str = "seats";
+    pub const BEST_EFFORT: &str = "best-effort";
+    pub const SORT: &str = "sort";
+    pub const REVERSE: &str = "reverse";
+    pub const WATCH_INTERVAL: &str = "watch-interval";
+    pub const OUTPUT: &str = "output";
+    pub const HEADER_FORMAT: &str = "header-format";
+    pub const HEADER_REPEAT: &str = "header-repeat";
+    pub const TIME_STYLE: &str = "time-style";
+    pub const VERSION_FULL: &str = "__version-full";
     pub const FILE: &str = "FILE"; // if length=1: FILE, if length=2: ARG1 ARG2
 }
 
@@ -62,6 +84,8 @@ pub fn uu_app() -> Command {
             Arg::new(options::BOOT)
                 .long(options::BOOT)
                 .short('b')
+                // Unique abbreviation in GNU who, ambiguous with --best-effort
+                .alias("b")
                 .help(translate!("who-help-boot"))
                 .action(ArgAction::SetTrue),
         )
@@ -69,6 +93,7 @@ pub fn uu_app() -> Command {
             Arg::new(options::DEAD)
                 .long(options::DEAD)
                 .short('d')
+                .aliases(["d", "de"])
                 .help(translate!("who-help-dead"))
                 .action(ArgAction::SetTrue),
         )
@@ -76,6 +101,9 @@ pub fn uu_app() -> Command {
             Arg::new(options::HEADING)
                 .long(options::HEADING)
                 .short('H')
+                // Unique abbreviations in GNU who, ambiguous with --header-format
+                // and --header-repeat
+                .aliases(["hea", "head"])
                 .help(translate!("who-help-heading"))
                 .action(ArgAction::SetTrue),
         )
@@ -98,6 +126,12 @@ pub fn uu_app() -> Command {
                 .help(translate!("who-help-only-hostname-user"))
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new(options::TTY)
+                .long(options::TTY)
+                .value_name("LINE")
+                .help(translate!("who-help-tty")),
+        )
         .arg(
             Arg::new(options::PROCESS)
                 .long(options::PROCESS)
@@ -109,13 +143,36 @@ pub fn uu_app() -> Command {
             Arg::new(options::COUNT)
                 .long(options::COUNT)
                 .short('q')
+                // Unique abbreviations in GNU who, which our extra long
+                // options would otherwise make ambiguous
+                .aliases(["c", "co", "cou", "coun"])
                 .help(translate!("who-help-count"))
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new(options::COUNT_BY_HOST)
+                .long(options::COUNT_BY_HOST)
+                .help(translate!("who-help-count-by-host"))
+                .conflicts_with_all([options::COUNT, options::OUTPUT])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::MAX_USERS)
+                .long(options::MAX_USERS)
+                .value_name("N")
+                .help(translate!("who-help-max-users"))
+                .conflicts_with_all([
+                    options::COUNT,
+                    options::COUNT_BY_HOST,
+                    options::OUTPUT,
+                    options::WATCH_INTERVAL,
+                ]),
+        )
         .arg(
             Arg::new(options::RUNLEVEL)
                 .long(options::RUNLEVEL)
                 .short('r')
+                .alias("r")
                 .help(get_runlevel_help())
                 .action(ArgAction::SetTrue),
         )
@@ -123,6 +180,7 @@ pub fn uu_app() -> Command {
             Arg::new(options::SHORT)
                 .long(options::SHORT)
                 .short('s')
+                .alias("s")
                 .help(translate!("who-help-short"))
                 .action(ArgAction::SetTrue),
         )
@@ -130,6 +188,8 @@ pub fn uu_app() -> Command {
             Arg::new(options::TIME)
                 .long(options::TIME)
                 .short('t')
+                // Unique abbreviations in GNU who, ambiguous with --time-style
+                .aliases(["t", "ti", "tim"])
                 .help(translate!("who-help-time"))
                 .action(ArgAction::SetTrue),
         )
@@ -137,6 +197,7 @@ pub fn uu_app() -> Command {
             Arg::new(options::USERS)
                 .long(options::USERS)
                 .short('u')
+                .alias("u")
                 .help(translate!("who-help-users"))
                 .action(ArgAction::SetTrue),
         )
@@ -146,9 +207,130 @@ pub fn uu_app() -> Command {
                 .short('T')
                 .visible_short_alias('w')
                 .visible_aliases(["message", "writable"])
+                // Unique abbreviations in GNU who, ambiguous with --min-idle
+                .aliases(["m", "w"])
                 .help(translate!("who-help-mesg"))
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new(options::COMMAND)
+                .long(options::COMMAND)
+                .help(translate!("who-help-command"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::DEDUPLICATE)
+                .long(options::DEDUPLICATE)
+                .help(translate!("who-help-deduplicate"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::UPTIME)
+                .long(options::UPTIME)
+                .help(translate!("who-help-uptime"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::NUMERIC_USER)
+                .long(options::NUMERIC_USER)
+                .help(translate!("who-help-numeric-user"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::TERSE)
+                .long(options::TERSE)
+                .visible_alias("no-pad")
+                .help(translate!("who-help-terse"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::SEATS)
+                .long(options::SEATS)
+                .help(translate!("who-help-seats"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::BEST_EFFORT)
+                .long(options::BEST_EFFORT)
+                .help(translate!("who-help-best-effort"))
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::SORT)
+                .long(options::SORT)
+                .value_name("FIELD")
+                .help(translate!("who-help-sort"))
+                .value_parser(["user", "line", "time", "host", "idle"]),
+        )
+        .arg(
+            Arg::new(options::REVERSE)
+                .long(options::REVERSE)
+                .help(translate!("who-help-reverse"))
+                .requires(options::SORT)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::IDLE_THRESHOLD)
+                .long(options::IDLE_THRESHOLD)
+                .value_name("DURATION")
+                .help(translate!("who-help-idle-threshold")),
+        )
+        .arg(
+            Arg::new(options::MIN_IDLE)
+                .long(options::MIN_IDLE)
+                .value_name("DURATION")
+                .help(translate!("who-help-min-idle")),
+        )
+        .arg(
+            Arg::new(options::RESOLVE_TIMEOUT)
+                .long(options::RESOLVE_TIMEOUT)
+                .value_name("DURATION")
+                .help(translate!("who-help-resolve-timeout")),
+        )
+        .arg(
+            Arg::new(options::HOST_WIDTH)
+                .long(options::HOST_WIDTH)
+                .value_name("BYTES")
+                .help(translate!("who-help-host-width")),
+        )
+        .arg(
+            Arg::new(options::WATCH_INTERVAL)
+                .long(options::WATCH_INTERVAL)
+                .value_name("DURATION")
+                .help(translate!("who-help-watch-interval")),
+        )
+        .arg(
+            Arg::new(options::OUTPUT)
+                .long(options::OUTPUT)
+                .value_name("FORMAT")
+                .help(translate!("who-help-output"))
+                .value_parser(["wtmp", "json"])
+                .conflicts_with(options::COUNT),
+        )
+        .arg(
+            Arg::new(options::HEADER_FORMAT)
+                .long(options::HEADER_FORMAT)
+                .value_name("TEMPLATE")
+                .help(translate!("who-help-header-format")),
+        )
+        .arg(
+            Arg::new(options::HEADER_REPEAT)
+                .long(options::HEADER_REPEAT)
+                .value_name("N")
+                .help(translate!("who-help-header-repeat")),
+        )
+        .arg(
+            Arg::new(options::TIME_STYLE)
+                .long(options::TIME_STYLE)
+                .value_name("STYLE")
+                .help(translate!("who-help-time-style")),
+        )
+        .arg(
+            Arg::new(options::VERSION_FULL)
+                .long(options::VERSION_FULL)
+                .hide(true)
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new(options::FILE)
                 .num_args(1..=2)
@@ -82,6 +82,20 @@ checksum-failed-open-file = { $count ->
 }
 checksum-error-algo-bad-format = { $file }: { $line }: improperly formatted { $algo } checksum line
 
+# Uptime line, shared by uptime and who
+uptime-output-unknown-uptime = up ???? days ??:??,
+uptime-output-up-text = up
+uptime-user-count = { $count ->
+    [one] 1 user
+   *[other] { $count } users
+}
+uptime-format = { $days ->
+    [0] { $time }
+    [one] { $days } day, { $time }
+   *[other] { $days } days { $time }
+}
+uptime-lib-format-loadavg = load average: { $avg1 }, { $avg5 }, { $avg15 }
+
 # uudoc tldr examples messages
 uudoc-tldr-attribution = The examples are provided by the [tldr-pages project](https://tldr.sh) under the [CC BY 4.0 License](https://github.com/tldr-pages/tldr/blob/main/LICENSE.md).
 uudoc-tldr-disclaimer = Please note that, as uutils is a work in progress, some examples might fail.
@@ -76,6 +76,20 @@ checksum-failed-open-file = { $count ->
 }
 checksum-error-algo-bad-format = { $file }: { $line }: ligne invalide pour { $algo }
 
+# Ligne de durée de fonctionnement, commune à uptime et who
+uptime-output-unknown-uptime = actif ???? jours ??:??,
+uptime-output-up-text = actif
+uptime-user-count = { $count ->
+    [one] 1 utilisateur
+   *[other] { $count } utilisateurs
+}
+uptime-format = { $days ->
+    [0] { $time }
+    [one] { $days } jour, { $time }
+   *[other] { $days } jours { $time }
+}
+uptime-lib-format-loadavg = charge moyenne : { $avg1 }, { $avg5 }, { $avg15 }
+
 # Messages uudoc pour les exemples tldr
 uudoc-tldr-attribution = Les exemples sont fournis par le [projet tldr-pages](https://tldr.sh) sous la [licence CC BY 4.0](https://github.com/tldr-pages/tldr/blob/main/LICENSE.md).
 uudoc-tldr-disclaimer = Veuillez noter que, uutils étant en cours de développement, certains exemples peuvent échouer.
@@ -7,9 +7,11 @@
 
 //! `human`-size formatting
 //!
-//! Format sizes like gnulibs human_readable() would
+//! Format sizes like gnulibs human_readable() would, and parse them back
+//! like `numfmt --from=auto` does
 
-use unit_prefix::NumberPrefix;
+use std::error::Error;
+use std::fmt;
 
 #[derive(Copy, Clone, PartialEq)]
 pub enum SizeFormat {
@@ -18,29 +20,56 @@ pub enum SizeFormat {
     Decimal, // Powers of 1000, --si
 }
 
+/// The prefixes from kilo up, as GNU writes them for powers of 1024
+const PREFIXES: [char; 10] = ['K', 'M', 'G', 'T', 'P', 'E', 'Z', 'Y', 'R', 'Q'];
+
+/// The most decimals [`to_iec`] and [`to_si`] give, so that the scaled
+/// value fits in a `u128`
+const MAX_PRECISION: usize = 18;
+
 /// There are a few peculiarities to how GNU formats the sizes:
-/// 1. One decimal place is given if and only if the size is smaller than 10
-/// 2. It rounds sizes up.
+/// 1. Decimals are given if and only if the size is smaller than 10
+/// 2. It rounds sizes up, unlike `numfmt`'s default of rounding half away
+///    from zero. A size rounding up to the next power is written with it,
+///    as `1.0M` rather than `1024K`.
 /// 3. The human-readable format uses powers for 1024, but does not display the "i"
 ///    that is commonly used to denote Kibi, Mebi, etc.
-/// 4. Kibi and Kilo are denoted differently ("k" and "K", respectively)
+/// 4. Kibi and Kilo are denoted differently ("K" and "k", respectively)
 /// 5. The decimal separator follows LC_NUMERIC ("1.3M" in C, "1,3M" in fr_FR)
-fn format_prefixed(prefixed: &NumberPrefix<f64>) -> String {
-    match prefixed {
-        NumberPrefix::Standalone(bytes) => bytes.to_string(),
-        NumberPrefix::Prefixed(prefix, bytes) => {
-            // Remove the "i" from "Ki", "Mi", etc. if present
-            let prefix_str = prefix.symbol().trim_end_matches('i');
-
-            // Check whether we get more than 10 if we round up to the first decimal
-            // because we want do display 9.81 as "9.9", not as "10".
-            if (10.0 * bytes).ceil() >= 100.0 {
-                format!("{:.0}{prefix_str}", bytes.ceil())
-            } else {
-                let number = format!("{:.1}", (10.0 * bytes).ceil() / 10.0);
-                format!("{}{prefix_str}", localize_decimal(number))
-            }
+fn format_prefixed(n: u64, base: u128, precision: usize, kilo: char) -> String {
+    let n = u128::from(n);
+    if n < base {
+        return n.to_string();
+    }
+    let precision = precision.min(MAX_PRECISION);
+    let scale = 10_u128.pow(precision as u32);
+
+    // The largest power of the base not above n
+    let mut exponent = 0;
+    let mut unit = base;
+    while n / unit >= base {
+        unit *= base;
+        exponent += 1;
+    }
+    loop {
+        let prefix = if exponent == 0 {
+            kilo
+        } else {
+            PREFIXES[exponent]
+        };
+        // Check whether we get 10 or more once rounded up to the decimals
+        // shown, because we want to display 9.81 as "9.9", not as "10".
+        let scaled = (n * scale).div_ceil(unit);
+        if precision > 0 && scaled < 10 * scale {
+            let number = format!("{}.{:0precision$}", scaled / scale, scaled % scale);
+            return format!("{}{prefix}", localize_decimal(number));
         }
+        let whole = n.div_ceil(unit);
+        if whole < base {
+            return format!("{whole}{prefix}");
+        }
+        unit *= base;
+        exponent += 1;
     }
 }
 
@@ -60,24 +89,192 @@ fn localize_decimal(s: String) -> String {
     }
 }
 
+/// `n` in powers of 1024 as `ls -h` writes sizes, e.g. `4.0K`, with
+/// `precision` decimals for values below 10 (GNU gives one)
+pub fn to_iec(n: u64, precision: usize) -> String {
+    format_prefixed(n, 1024, precision, 'K')
+}
+
+/// `n` in powers of 1000 as `ls --si` writes sizes, e.g. `4.1k`, with
+/// `precision` decimals for values below 10 (GNU gives one)
+pub fn to_si(n: u64, precision: usize) -> String {
+    format_prefixed(n, 1000, precision, 'k')
+}
+
 pub fn human_readable(size: u64, sfmt: SizeFormat) -> String {
     match sfmt {
-        SizeFormat::Binary => format_prefixed(&NumberPrefix::binary(size as f64)),
-        SizeFormat::Decimal => format_prefixed(&NumberPrefix::decimal(size as f64)),
+        SizeFormat::Binary => to_iec(size, 1),
+        SizeFormat::Decimal => to_si(size, 1),
         SizeFormat::Bytes => size.to_string(),
     }
 }
 
+/// Why [`from_human`] rejected a number
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// Not a number with an optional suffix
+    Invalid(String),
+    /// More than a `u64` holds
+    Overflow(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(s) => write!(f, "invalid suffix in input: '{s}'"),
+            Self::Overflow(s) => write!(f, "value too large to be converted: '{s}'"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parse a number like `numfmt --from=auto`: `K` (or `k`), `M`, `G`...
+/// are powers of 1000 and `Ki`, `Mi`, `Gi`... powers of 1024, e.g. `4K` is
+/// 4000 and `1.5Ki` is 1536. Fractions of a unit are rounded up.
+pub fn from_human(s: &str) -> Result<u64, ParseError> {
+    let invalid = || ParseError::Invalid(s.to_string());
+    let overflow = || ParseError::Overflow(s.to_string());
+
+    let end = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, suffix) = s.split_at(end);
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() && fraction.is_empty() || fraction.contains('.') {
+        return Err(invalid());
+    }
+
+    let multiplier = match suffix.chars().next() {
+        None => 1,
+        Some(letter) => {
+            let exponent = match letter {
+                'k' => 1,
+                _ => {
+                    PREFIXES
+                        .iter()
+                        .position(|&p| p == letter)
+                        .ok_or_else(invalid)?
+                        + 1
+                }
+            };
+            // As in numfmt, only the SI kilo may be written `k`
+            let base: u128 = match &suffix[letter.len_utf8()..] {
+                "" => 1000,
+                "i" if letter != 'k' => 1024,
+                _ => return Err(invalid()),
+            };
+            base.pow(exponent as u32)
+        }
+    };
+
+    let digits = |s: &str| -> Result<u128, ParseError> {
+        s.bytes().try_fold(0_u128, |value, digit| {
+            value
+                .checked_mul(10)
+                .and_then(|value| value.checked_add(u128::from(digit - b'0')))
+                .ok_or_else(overflow)
+        })
+    };
+    let scale = u32::try_from(fraction.len())
+        .ok()
+        .and_then(|len| 10_u128.checked_pow(len))
+        .ok_or_else(overflow)?;
+    let value = digits(whole)?
+        .checked_mul(scale)
+        .and_then(|value| value.checked_add(digits(fraction).ok()?))
+        .and_then(|value| value.checked_mul(multiplier))
+        .ok_or_else(overflow)?
+        .div_ceil(scale);
+    u64::try_from(value).map_err(|_| overflow())
+}
+
 #[cfg(test)]
-#[test]
-fn test_human_readable() {
-    let test_cases = [
-        (133_456_345, SizeFormat::Binary, "128M"),
-        (12 * 1024 * 1024, SizeFormat::Binary, "12M"),
-        (8500, SizeFormat::Binary, "8.4K"),
-    ];
-
-    for &(size, sfmt, expected_str) in &test_cases {
-        assert_eq!(human_readable(size, sfmt), expected_str);
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_readable() {
+        let test_cases = [
+            (133_456_345, SizeFormat::Binary, "128M"),
+            (12 * 1024 * 1024, SizeFormat::Binary, "12M"),
+            (8500, SizeFormat::Binary, "8.4K"),
+        ];
+
+        for &(size, sfmt, expected_str) in &test_cases {
+            assert_eq!(human_readable(size, sfmt), expected_str);
+        }
+    }
+
+    #[test]
+    fn test_to_iec() {
+        for (n, expected) in [
+            (0, "0"),
+            (1023, "1023"),
+            (1024, "1.0K"),
+            (1025, "1.1K"),
+            (1536, "1.5K"),
+            (1537, "1.6K"),
+            (10 * 1024 - 1, "10K"),
+            (10 * 1024, "10K"),
+            (10 * 1024 + 1, "11K"),
+            (1024 * 1024 - 1, "1.0M"),
+            (1024 * 1024, "1.0M"),
+            (u64::MAX, "16E"),
+        ] {
+            assert_eq!(to_iec(n, 1), expected, "{n}");
+        }
+        assert_eq!(to_iec(1536, 0), "2K");
+        assert_eq!(to_iec(1536, 2), "1.50K");
+        assert_eq!(to_iec(1537, 2), "1.51K");
+    }
+
+    #[test]
+    fn test_to_si() {
+        for (n, expected) in [
+            (999, "999"),
+            (1000, "1.0k"),
+            (1001, "1.1k"),
+            (1500, "1.5k"),
+            (4096, "4.1k"),
+            (9999, "10k"),
+            (999_999, "1.0M"),
+            (1_000_000, "1.0M"),
+        ] {
+            assert_eq!(to_si(n, 1), expected, "{n}");
+        }
+    }
+
+    #[test]
+    fn test_from_human() {
+        for (s, expected) in [
+            ("0", 0),
+            ("123", 123),
+            ("4K", 4000),
+            ("4k", 4000),
+            ("4Ki", 4096),
+            ("1.5Ki", 1536),
+            ("1.1k", 1100),
+            ("1.0001k", 1001),
+            ("0.5", 1),
+            (".5K", 500),
+            ("2M", 2_000_000),
+            ("15Ei", 15 << 60),
+        ] {
+            assert_eq!(from_human(s), Ok(expected), "{s}");
+        }
+        for s in ["", ".", "K", "4X", "4KiB", "4ki", "1.2.3", "-1", " 1"] {
+            assert_eq!(from_human(s), Err(ParseError::Invalid(s.to_string())));
+        }
+        for s in ["16Ei", "1Q", "99999999999999999999999999999999999999999"] {
+            assert_eq!(from_human(s), Err(ParseError::Overflow(s.to_string())));
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for n in [1024, 1536, 4096, 1024 * 1024] {
+            assert!(from_human(&format!("{}i", to_iec(n, 1))).unwrap() >= n);
+        }
     }
 }
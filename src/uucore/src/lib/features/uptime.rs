@@ -45,48 +45,6 @@ pub fn get_formatted_time() -> String {
         .to_string()
 }
 
-/// Safely get macOS boot time using sysctl command
-///
-/// This function uses the sysctl command-line tool to retrieve the kernel
-/// boot time on macOS, avoiding any unsafe code. It parses the output
-/// of the sysctl command to extract the boot time.
-///
-/// # Returns
-///
-/// Returns Some(time_t) if successful, None if the call fails.
-#[cfg(target_os = "macos")]
-fn get_macos_boot_time_sysctl() -> Option<time_t> {
-    use std::process::Command;
-
-    // Execute sysctl command to get boot time
-    let output = Command::new("sysctl")
-        .arg("-n")
-        .arg("kern.boottime")
-        .output();
-
-    if let Ok(output) = output {
-        if output.status.success() {
-            // Parse output format: { sec = 1729338352, usec = 0 } Wed Oct 19 08:25:52 2025
-            // We need to extract the seconds value from the structured output
-            let stdout = String::from_utf8_lossy(&output.stdout);
-
-            // Extract the seconds from the output
-            // Look for "sec = " pattern
-            if let Some(sec_start) = stdout.find("sec = ") {
-                let sec_part = &stdout[sec_start + 6..];
-                if let Some(sec_end) = sec_part.find(',') {
-                    let sec_str = &sec_part[..sec_end];
-                    if let Ok(boot_time) = sec_str.trim().parse::<i64>() {
-                        return Some(boot_time as time_t);
-                    }
-                }
-            }
-        }
-    }
-
-    None
-}
-
 /// Get the system uptime
 ///
 /// # Arguments
@@ -136,10 +94,9 @@ pub fn get_uptime(_boot_time: Option<time_t>) -> UResult<i64> {
 #[cfg(unix)]
 #[cfg(not(target_os = "openbsd"))]
 pub fn get_uptime(boot_time: Option<time_t>) -> UResult<i64> {
-    use crate::utmpx::BOOT_TIME;
-    use crate::utmpx::Utmpx;
     use std::fs::File;
     use std::io::Read;
+    use std::time::UNIX_EPOCH;
 
     let mut proc_uptime_s = String::new();
 
@@ -153,48 +110,38 @@ pub fn get_uptime(boot_time: Option<time_t>) -> UResult<i64> {
         return Ok(uptime);
     }
 
-    // Try provided boot_time or derive from utmpx
+    // Try provided boot_time, or else the login records, systemd, /proc/stat
+    // and macOS's kern.boottime
     let derived_boot_time = boot_time.or_else(|| {
-        Utmpx::iter_all_records()
-            .filter(|r| r.record_type() == BOOT_TIME)
-            .map(|r| r.login_time().unix_timestamp())
-            .find(|&ts| ts > 0)
-            .map(|ts| ts as time_t)
+        crate::utmpx::boot_time()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|since_epoch| since_epoch.as_secs() as time_t)
     });
 
-    // macOS-specific fallback: use sysctl kern.boottime when utmpx did not provide BOOT_TIME
-    //
-    // On macOS, the utmpx BOOT_TIME record can be unreliable or absent, causing intermittent
-    // test failures (see issue #3621: https://github.com/uutils/coreutils/issues/3621).
-    // The sysctl(CTL_KERN, KERN_BOOTTIME) approach is the canonical way to retrieve boot time
-    // on macOS and is always available, making uptime more reliable on this platform.
-    //
-    // This fallback only runs if utmpx failed to provide a boot time.
-    #[cfg(target_os = "macos")]
-    let derived_boot_time = {
-        let mut t = derived_boot_time;
-        if t.is_none() {
-            // Use a safe wrapper function to get boot time via sysctl
-            if let Some(boot_time) = get_macos_boot_time_sysctl() {
-                t = Some(boot_time);
-            }
-        }
-        t
-    };
+    uptime_since_boot(derived_boot_time, Timestamp::now().as_second())
+}
 
-    if let Some(t) = derived_boot_time {
-        let now = Timestamp::now().as_second();
-        #[cfg(target_pointer_width = "64")]
-        let boottime: i64 = t;
-        #[cfg(not(target_pointer_width = "64"))]
-        let boottime: i64 = t.into();
-        if now < boottime {
-            Err(UptimeError::BootTime)?;
+/// The seconds from `boot_time` to `now`
+///
+/// A boot time at or before the epoch is as good as unknown: it's what
+/// records without one carry, and would count decades of uptime.
+#[cfg(unix)]
+#[cfg(not(target_os = "openbsd"))]
+fn uptime_since_boot(boot_time: Option<time_t>, now: i64) -> UResult<i64> {
+    #[cfg(target_pointer_width = "64")]
+    let boot_time: Option<i64> = boot_time;
+    #[cfg(not(target_pointer_width = "64"))]
+    let boot_time: Option<i64> = boot_time.map(Into::into);
+    match boot_time {
+        Some(boot_time) if boot_time > 0 => {
+            if now < boot_time {
+                Err(UptimeError::BootTime)?;
+            }
+            Ok(now - boot_time)
         }
-        return Ok(now - boottime);
+        _ => Err(UptimeError::SystemUptime)?,
     }
-
-    Err(UptimeError::SystemUptime)?
 }
 
 /// The format used to display a FormattedUptime.
@@ -299,16 +246,12 @@ pub fn get_formatted_uptime(
 #[cfg(not(target_os = "openbsd"))]
 // see: https://gitlab.com/procps-ng/procps/-/blob/4740a0efa79cade867cfc7b32955fe0f75bf5173/library/uptime.c#L63-L115
 pub fn get_nusers() -> usize {
-    use crate::utmpx::USER_PROCESS;
-    use crate::utmpx::Utmpx;
+    use crate::utmpx::{Utmpx, UtmpxRecord};
 
-    let mut num_user = 0;
-    Utmpx::iter_all_records().for_each(|ut| {
-        if ut.record_type() == USER_PROCESS {
-            num_user += 1;
-        }
-    });
-    num_user
+    // Count the records `who` lists as users
+    Utmpx::iter_all_records()
+        .filter(UtmpxRecord::is_user_process)
+        .count()
 }
 
 /// Get the number of users currently logged in
@@ -468,15 +411,52 @@ pub fn get_loadavg() -> UResult<(f64, f64, f64)> {
 /// e.g. "load average: 0.00, 0.00, 0.00"
 #[inline]
 pub fn get_formatted_loadavg() -> UResult<String> {
-    let loadavg = get_loadavg()?;
+    Ok(format_loadavg(get_loadavg()?))
+}
+
+/// Format a load average, e.g. "load average: 0.00, 0.00, 0.00"
+fn format_loadavg(loadavg: (f64, f64, f64)) -> String {
     let mut args = fluent::FluentArgs::new();
     args.set("avg1", format!("{:.2}", loadavg.0));
     args.set("avg5", format!("{:.2}", loadavg.1));
     args.set("avg15", format!("{:.2}", loadavg.2));
-    Ok(crate::locale::get_message_with_args(
-        "uptime-lib-format-loadavg",
-        args,
-    ))
+    crate::locale::get_message_with_args("uptime-lib-format-loadavg", args)
+}
+
+/// Format the line `uptime` prints, which `w` starts with too, e.g.
+/// " 12:34:56  up  2 days 03:04,  2 users,  load average: 0.10, 0.20, 0.30"
+///
+/// An unknown `uptime` is shown as `????`, and an unknown load average
+/// leaves its clause out.
+pub fn format_header(
+    time: &str,
+    uptime: Option<i64>,
+    nusers: usize,
+    loadavg: Option<(f64, f64, f64)>,
+) -> String {
+    let uptime = match uptime.filter(|&uptime| uptime >= 0) {
+        Some(uptime) => format!(
+            "{}  {},",
+            translate!("uptime-output-up-text"),
+            FormattedUptime::new(uptime).get_human_readable_uptime()
+        ),
+        None => translate!("uptime-output-unknown-uptime"),
+    };
+    let mut header = format!(" {time}  {uptime}  {}", format_nusers(nusers));
+    if let Some(loadavg) = loadavg {
+        header.push_str(",  ");
+        header.push_str(&format_loadavg(loadavg));
+    }
+    header
+}
+
+/// Get the line `uptime` prints, which `w` starts with too, from the
+/// current time, the given `uptime` (e.g. from [`get_uptime`]), the given
+/// number of users (e.g. from [`get_nusers`]) and the load average
+///
+/// See [`format_header`].
+pub fn get_formatted_header(uptime: Option<i64>, nusers: usize) -> String {
+    format_header(&get_formatted_time(), uptime, nusers, get_loadavg().ok())
 }
 
 #[cfg(test)]
@@ -495,35 +475,59 @@ mod tests {
         assert_eq!("2 users", format_nusers(2));
     }
 
-    /// Test that sysctl kern.boottime is accessible on macOS and returns valid boot time.
-    /// This ensures the fallback mechanism added for issue #3621 works correctly.
     #[test]
-    #[cfg(target_os = "macos")]
-    fn test_macos_sysctl_boottime_available() {
-        // Test the safe wrapper function
-        let boot_time = get_macos_boot_time_sysctl();
-
-        // Verify the safe wrapper succeeded
-        assert!(
-            boot_time.is_some(),
-            "get_macos_boot_time_sysctl should succeed on macOS"
+    fn test_format_header() {
+        unsafe {
+            std::env::set_var("LANG", "en_US.UTF-8");
+        }
+        let _ = locale::setup_localization("uptime");
+        assert_eq!(
+            format_header(
+                "12:34:56",
+                Some(2 * 86400 + 3 * 3600 + 4 * 60),
+                2,
+                Some((0.1, 0.2, 0.3))
+            ),
+            " 12:34:56  up  2 days 03:04,  2 users,  load average: 0.10, 0.20, 0.30"
         );
+        assert_eq!(
+            format_header("12:34:56", Some(59), 1, None),
+            " 12:34:56  up  00:00,  1 user"
+        );
+        assert_eq!(
+            format_header("12:34:56", None, 0, None),
+            " 12:34:56  up ???? days ??:??,  0 users"
+        );
+    }
 
-        let boot_time = boot_time.unwrap();
-
-        // Verify boot time is valid (positive, reasonable value)
-        assert!(boot_time > 0, "Boot time should be positive");
-
-        // Boot time should be after 2000-01-01 (946684800 seconds since epoch)
+    #[test]
+    fn test_formatted_header() {
+        unsafe {
+            std::env::set_var("LANG", "en_US.UTF-8");
+        }
+        let _ = locale::setup_localization("uptime");
+        // Only the current time and the load average come from the system.
+        let header = get_formatted_header(Some(3 * 3600 + 4 * 60), 3);
+        assert!(header.contains("  up  03:04,  3 users"), "{header:?}");
+        let header = get_formatted_header(None, 1);
         assert!(
-            boot_time > 946_684_800,
-            "Boot time should be after year 2000"
+            header.contains("  up ???? days ??:??,  1 user"),
+            "{header:?}"
         );
+    }
 
-        // Boot time should be before current time
-        let boot_time = Timestamp::from_second(boot_time).unwrap();
-        let now = Timestamp::now();
-        assert!(boot_time < now, "Boot time should be before current time");
+    #[test]
+    #[cfg(unix)]
+    #[cfg(not(target_os = "openbsd"))]
+    fn test_uptime_since_boot() {
+        assert_eq!(
+            uptime_since_boot(Some(1_500_000_000), 1_500_090_061).unwrap(),
+            90_061
+        );
+        assert!(uptime_since_boot(Some(1_500_000_001), 1_500_000_000).is_err());
+        // Without a boot time, rather than counting from the epoch
+        assert!(uptime_since_boot(None, 1_500_000_000).is_err());
+        assert!(uptime_since_boot(Some(0), 1_500_000_000).is_err());
     }
 
     /// Test that get_uptime always succeeds on macOS due to sysctl fallback.
@@ -3,7 +3,7 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 //
-// spell-checker:ignore IDLEN logind
+// spell-checker:ignore IDLEN logind boottime usec
 
 //! Aims to provide platform-independent methods to obtain login records
 //!
@@ -34,12 +34,21 @@
 pub extern crate time;
 
 use std::ffi::CString;
+use std::fs::{self, File, OpenOptions};
 use std::io::Result as IOResult;
+use std::io::{self, Read, Seek, Write};
 use std::marker::PhantomData;
+use std::os::fd::AsRawFd;
 use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::FileExt;
 use std::path::Path;
 use std::ptr;
-use std::sync::{Mutex, MutexGuard};
+use std::sync::{Mutex, MutexGuard, mpsc};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::display::Quotable;
+use crate::error::{FromIo, UResult, USimpleError};
 
 #[cfg(feature = "feat_systemd_logind")]
 use crate::features::systemd_logind;
@@ -203,9 +212,84 @@ mod ut {
     pub use libc::USER_PROCESS;
 }
 
+/// The type of a login record, A.K.A. ut.ut_type
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordType {
+    /// `BOOT_TIME`: the system booted
+    BootTime,
+    /// `RUN_LVL`: the run level changed, or the system was shut down
+    #[cfg(not(target_os = "freebsd"))]
+    RunLevel,
+    /// `NEW_TIME`: the clock was changed, to the time of the record
+    NewTime,
+    /// `OLD_TIME`: the clock was changed, from the time of the record
+    OldTime,
+    /// `INIT_PROCESS`: a process spawned by init
+    InitProcess,
+    /// `LOGIN_PROCESS`: a process waiting for a user to log in
+    LoginProcess,
+    /// `USER_PROCESS`: a user's login
+    UserProcess,
+    /// `DEAD_PROCESS`: a process that has exited, like a logout
+    DeadProcess,
+    /// Any other type, like `EMPTY`, by its raw value
+    Other(i16),
+}
+
+impl From<i16> for RecordType {
+    fn from(value: i16) -> Self {
+        match value {
+            x if x == BOOT_TIME => Self::BootTime,
+            #[cfg(not(target_os = "freebsd"))]
+            x if x == RUN_LVL => Self::RunLevel,
+            x if x == NEW_TIME => Self::NewTime,
+            x if x == OLD_TIME => Self::OldTime,
+            x if x == INIT_PROCESS => Self::InitProcess,
+            x if x == LOGIN_PROCESS => Self::LoginProcess,
+            x if x == USER_PROCESS => Self::UserProcess,
+            x if x == DEAD_PROCESS => Self::DeadProcess,
+            x => Self::Other(x),
+        }
+    }
+}
+
+impl From<RecordType> for i16 {
+    fn from(record_type: RecordType) -> Self {
+        match record_type {
+            RecordType::BootTime => BOOT_TIME,
+            #[cfg(not(target_os = "freebsd"))]
+            RecordType::RunLevel => RUN_LVL,
+            RecordType::NewTime => NEW_TIME,
+            RecordType::OldTime => OLD_TIME,
+            RecordType::InitProcess => INIT_PROCESS,
+            RecordType::LoginProcess => LOGIN_PROCESS,
+            RecordType::UserProcess => USER_PROCESS,
+            RecordType::DeadProcess => DEAD_PROCESS,
+            RecordType::Other(x) => x,
+        }
+    }
+}
+
+/// Where a login record was read from, to tell backends apart when
+/// debugging their output
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordSource {
+    /// The system's utmp database
+    Utmp,
+    /// systemd-logind
+    SystemdLogind,
+    /// Derived from `/proc`, like a boot record made from its `btime`
+    Proc,
+    /// Derived from `sysctl`, like a boot record made from `kern.boottime`
+    Sysctl,
+    /// A utmp or wtmp file other than the system's database
+    File,
+}
+
 /// A login record
 pub struct Utmpx {
     inner: utmpx,
+    source: RecordSource,
 }
 
 #[cfg(target_os = "netbsd")]
@@ -218,6 +302,15 @@ impl Utmpx {
     }
 }
 
+/// Set `ut.ut_type` and `ut.ut_name`, NetBSD's `ut_user`
+#[cfg(target_os = "netbsd")]
+fn set_ut_type_user(ut: &mut utmpx, ut_type: i16, user: &[u8]) {
+    ut.ut_type = ut_type as _;
+    for (dst, src) in ut.ut_name.iter_mut().zip(user) {
+        *dst = *src as libc::c_char;
+    }
+}
+
 #[cfg(not(target_os = "netbsd"))]
 impl Utmpx {
     fn ut_type(&self) -> i16 {
@@ -228,11 +321,28 @@ impl Utmpx {
     }
 }
 
+/// Set `ut.ut_type` and `ut.ut_user`
+#[cfg(not(target_os = "netbsd"))]
+fn set_ut_type_user(ut: &mut utmpx, ut_type: i16, user: &[u8]) {
+    ut.ut_type = ut_type;
+    for (dst, src) in ut.ut_user.iter_mut().zip(user) {
+        *dst = *src as libc::c_char;
+    }
+}
+
 impl Utmpx {
     /// A.K.A. ut.ut_type
     pub fn record_type(&self) -> i16 {
         self.ut_type()
     }
+    /// A.K.A. ut.ut_type, as a [`RecordType`]
+    pub fn kind(&self) -> RecordType {
+        self.ut_type().into()
+    }
+    /// Where the record was read from
+    pub fn source(&self) -> RecordSource {
+        self.source
+    }
     /// A.K.A. ut.ut_pid
     pub fn pid(&self) -> i32 {
         self.inner.ut_pid
@@ -285,41 +395,12 @@ impl Utmpx {
     }
     /// check if the record is a user process
     pub fn is_user_process(&self) -> bool {
-        !self.user().is_empty() && self.record_type() == USER_PROCESS
+        !self.user().is_empty() && self.kind() == RecordType::UserProcess
     }
 
     /// Canonicalize host name using DNS
     pub fn canon_host(&self) -> IOResult<String> {
-        let host = self.host();
-
-        let (hostname, display) = host.split_once(':').unwrap_or((&host, ""));
-
-        if !hostname.is_empty() {
-            use dns_lookup::{AddrInfoHints, getaddrinfo};
-
-            const AI_CANONNAME: i32 = 0x2;
-            let hints = AddrInfoHints {
-                flags: AI_CANONNAME,
-                ..AddrInfoHints::default()
-            };
-            if let Ok(sockets) = getaddrinfo(Some(hostname), None, Some(hints)) {
-                let sockets = sockets.collect::<IOResult<Vec<_>>>()?;
-                for socket in sockets {
-                    if let Some(ai_canonname) = socket.canonname {
-                        return Ok(if display.is_empty() {
-                            ai_canonname
-                        } else {
-                            format!("{ai_canonname}:{display}")
-                        });
-                    }
-                }
-            } else {
-                // GNU coreutils has this behavior
-                return Ok(hostname.to_string());
-            }
-        }
-
-        Ok(host)
+        canonicalize_host(&self.host())
     }
 
     /// Iterate through all the utmp records.
@@ -341,7 +422,7 @@ impl Utmpx {
 
         #[cfg(not(feature = "feat_systemd_logind"))]
         {
-            let iter = UtmpxIter::new();
+            let iter = UtmpxIter::new(RecordSource::Utmp);
             unsafe {
                 // This can technically fail, and it would be nice to detect that,
                 // but it doesn't return anything so we'd have to do nasty things
@@ -373,8 +454,21 @@ impl Utmpx {
             }
         }
 
-        let iter = UtmpxIter::new();
-        let path = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+        Self::iter_file_records(path.as_ref())
+    }
+
+    /// Iterate through the records of the utmp file at `path`, without
+    /// going to systemd-logind for the default one.
+    ///
+    /// The same caveats as for [`Utmpx::iter_all_records`] apply.
+    pub fn iter_file_records(path: &Path) -> UtmpxIter {
+        let source = if path == Path::new(DEFAULT_FILE) {
+            RecordSource::Utmp
+        } else {
+            RecordSource::File
+        };
+        let iter = UtmpxIter::new(source);
+        let path = CString::new(path.as_os_str().as_bytes()).unwrap();
         unsafe {
             // In glibc, utmpxname() only fails if there's not enough memory
             // to copy the string.
@@ -391,6 +485,110 @@ impl Utmpx {
         }
         iter
     }
+
+    /// Read the records of the utmp, wtmp or btmp file at `path` directly,
+    /// without the libc database functions
+    ///
+    /// The file is read whole, as the host's `struct utmpx` in its own byte
+    /// order, like GNU does. A partial record at the end, as left by a write
+    /// in progress, is ignored. Unlike [`Utmpx::iter_file_records`], this
+    /// reports a file that can't be read and doesn't block other iterators.
+    pub fn iter_from_file(path: &Path) -> UResult<impl Iterator<Item = Self>> {
+        let bytes = fs::read(path).map_err_context(|| path.maybe_quote().to_string())?;
+        let size = size_of::<utmpx>();
+        Ok((0..bytes.len() / size).map(move |i| {
+            let record = &bytes[i * size..(i + 1) * size];
+            // SAFETY: the slice holds size_of::<utmpx>() bytes, and utmpx is
+            // a plain C struct, for which any bytes are valid.
+            let inner = unsafe { ptr::read_unaligned(record.as_ptr().cast::<utmpx>()) };
+            Self {
+                inner,
+                source: RecordSource::File,
+            }
+        }))
+    }
+
+    /// The record as laid out on disk
+    fn as_bytes(&self) -> &[u8] {
+        // SAFETY: the bytes are those of `self.inner`, borrowed with it.
+        unsafe { std::slice::from_raw_parts(ptr::from_ref(&self.inner).cast(), size_of::<utmpx>()) }
+    }
+}
+
+/// Append `record` to the utmp or wtmp file at `path`, creating it if
+/// needed, like `updwtmpx()`
+///
+/// The file is locked while writing, and a record that can't be written
+/// whole is truncated away rather than left half written.
+pub fn put_utline(record: &Utmpx, path: &Path) -> UResult<()> {
+    let context = || path.maybe_quote().to_string();
+    let file = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .map_err_context(context)?;
+    lock_for_writing(&file).map_err_context(context)?;
+    append_record(&file, record).map_err_context(context)
+}
+
+/// Write `record` over the record of the same line in the utmp file at
+/// `path`, or append it if there is none, like `pututxline()`
+///
+/// The file is locked while it is searched and written.
+pub fn update_utline(record: &Utmpx, path: &Path) -> UResult<()> {
+    let context = || path.maybe_quote().to_string();
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err_context(context)?;
+    lock_for_writing(&file).map_err_context(context)?;
+
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err_context(context)?;
+    let size = size_of::<utmpx>();
+    let line = record.tty_device();
+    let same_line = (0..bytes.len() / size).find(|&i| {
+        // SAFETY: as in `Utmpx::iter_from_file`
+        let other = unsafe { ptr::read_unaligned(bytes[i * size..].as_ptr().cast::<utmpx>()) };
+        chars2string!(other.ut_line) == line
+    });
+    match same_line {
+        Some(i) => file.write_all_at(record.as_bytes(), (i * size) as u64),
+        // A partial record at the end would misalign the new one.
+        None => file
+            .set_len((bytes.len() / size * size) as u64)
+            .and_then(|()| append_record(&file, record)),
+    }
+    .map_err_context(context)
+}
+
+/// Take the `fcntl()` write lock on the whole of `file` that glibc's utmp
+/// writers take, waiting for it if need be; it's released with the file
+fn lock_for_writing(file: &File) -> io::Result<()> {
+    // SAFETY: flock is a plain C struct, for which all zeroes is valid.
+    let mut lock: libc::flock = unsafe { std::mem::zeroed() };
+    // The constants are c_short already on some platforms.
+    #[allow(clippy::unnecessary_cast)]
+    {
+        lock.l_type = libc::F_WRLCK as libc::c_short;
+        lock.l_whence = libc::SEEK_SET as libc::c_short;
+    }
+    // SAFETY: the descriptor is open, and `lock` is a valid flock.
+    if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_SETLKW, &lock) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Append `record` to the locked `file`, truncating a partial write away
+fn append_record(mut file: &File, record: &Utmpx) -> io::Result<()> {
+    let len = file.seek(io::SeekFrom::End(0))?;
+    file.write_all(record.as_bytes()).inspect_err(|_| {
+        let _ = file.set_len(len);
+    })
 }
 
 // On some systems these functions are not thread-safe. On others they're
@@ -409,12 +607,14 @@ pub struct UtmpxIter {
     /// Ensure UtmpxIter is !Send. Technically redundant because MutexGuard
     /// is also !Send.
     phantom: PhantomData<std::rc::Rc<()>>,
+    /// The source of the records read with `getutxent()`
+    source: RecordSource,
     #[cfg(feature = "feat_systemd_logind")]
     systemd_iter: Option<systemd_logind::SystemdUtmpxIter>,
 }
 
 impl UtmpxIter {
-    fn new() -> Self {
+    fn new(source: RecordSource) -> Self {
         // PoisonErrors can safely be ignored
         let guard = LOCK
             .lock()
@@ -422,6 +622,7 @@ impl UtmpxIter {
         Self {
             guard,
             phantom: PhantomData,
+            source,
             #[cfg(feature = "feat_systemd_logind")]
             systemd_iter: None,
         }
@@ -444,6 +645,7 @@ impl UtmpxIter {
         Self {
             guard,
             phantom: PhantomData,
+            source: RecordSource::SystemdLogind,
             systemd_iter: Some(systemd_iter),
         }
     }
@@ -456,6 +658,12 @@ pub enum UtmpxRecord {
     Systemd(systemd_logind::SystemdUtmpxCompat),
 }
 
+impl From<Utmpx> for UtmpxRecord {
+    fn from(utmpx: Utmpx) -> Self {
+        Self::Traditional(Box::new(utmpx))
+    }
+}
+
 impl UtmpxRecord {
     /// A.K.A. ut.ut_type
     pub fn record_type(&self) -> i16 {
@@ -466,6 +674,15 @@ impl UtmpxRecord {
         }
     }
 
+    /// A.K.A. ut.ut_type, as a [`RecordType`]
+    pub fn kind(&self) -> RecordType {
+        match self {
+            Self::Traditional(utmpx) => utmpx.kind(),
+            #[cfg(feature = "feat_systemd_logind")]
+            Self::Systemd(systemd) => systemd.kind(),
+        }
+    }
+
     /// A.K.A. ut.ut_pid
     pub fn pid(&self) -> i32 {
         match self {
@@ -493,6 +710,41 @@ impl UtmpxRecord {
         }
     }
 
+    /// Uid of the user, if the record carries one
+    ///
+    /// Only records read from systemd-logind do; utmp records just have a
+    /// user name, which callers have to look up themselves.
+    pub fn uid(&self) -> Option<u32> {
+        match self {
+            Self::Traditional(_) => None,
+            #[cfg(feature = "feat_systemd_logind")]
+            Self::Systemd(systemd) => systemd.uid(),
+        }
+    }
+
+    /// Whether the session's screen is locked
+    ///
+    /// Only known for records read from systemd-logind; `false` otherwise.
+    pub fn is_locked(&self) -> bool {
+        match self {
+            Self::Traditional(_) => false,
+            #[cfg(feature = "feat_systemd_logind")]
+            Self::Systemd(systemd) => systemd.is_locked(),
+        }
+    }
+
+    /// Since when the session has been idle
+    ///
+    /// Only known for records read from systemd-logind; `None` otherwise, and
+    /// for sessions that aren't idle.
+    pub fn idle_since(&self) -> Option<SystemTime> {
+        match self {
+            Self::Traditional(_) => None,
+            #[cfg(feature = "feat_systemd_logind")]
+            Self::Systemd(systemd) => systemd.idle_since(),
+        }
+    }
+
     /// A.K.A. ut.ut_host
     pub fn host(&self) -> String {
         match self {
@@ -531,6 +783,15 @@ impl UtmpxRecord {
         }
     }
 
+    /// Where the record was read from
+    pub fn source(&self) -> RecordSource {
+        match self {
+            Self::Traditional(utmpx) => utmpx.source(),
+            #[cfg(feature = "feat_systemd_logind")]
+            Self::Systemd(systemd) => systemd.source(),
+        }
+    }
+
     /// check if the record is a user process
     pub fn is_user_process(&self) -> bool {
         match self {
@@ -540,14 +801,452 @@ impl UtmpxRecord {
         }
     }
 
+    /// Whether the record is of a process that has exited, like a logout in
+    /// utmp or a closing session in logind
+    pub fn is_dead_process(&self) -> bool {
+        self.kind() == RecordType::DeadProcess
+    }
+
     /// Canonicalize host name using DNS
     pub fn canon_host(&self) -> IOResult<String> {
         match self {
             Self::Traditional(utmpx) => utmpx.canon_host(),
             #[cfg(feature = "feat_systemd_logind")]
-            Self::Systemd(systemd) => Ok(systemd.canon_host()),
+            Self::Systemd(systemd) => systemd.canon_host(),
+        }
+    }
+
+    /// Like [`Self::canon_host`], giving up on DNS after `timeout`
+    pub fn canon_host_within(&self, timeout: Duration) -> IOResult<String> {
+        match self {
+            Self::Traditional(utmpx) => canonicalize_host_within(&utmpx.host(), timeout),
+            #[cfg(feature = "feat_systemd_logind")]
+            Self::Systemd(systemd) => systemd.canon_host_within(timeout),
+        }
+    }
+}
+
+/// Canonicalize the host part of a `host[:display]` string using DNS
+pub(crate) fn canonicalize_host(host: &str) -> IOResult<String> {
+    let (hostname, display) = host.split_once(':').unwrap_or((host, ""));
+
+    if !hostname.is_empty() {
+        use dns_lookup::{AddrInfoHints, getaddrinfo};
+
+        const AI_CANONNAME: i32 = 0x2;
+        let hints = AddrInfoHints {
+            flags: AI_CANONNAME,
+            ..AddrInfoHints::default()
+        };
+        if let Ok(sockets) = getaddrinfo(Some(hostname), None, Some(hints)) {
+            let sockets = sockets.collect::<IOResult<Vec<_>>>()?;
+            for socket in sockets {
+                if let Some(ai_canonname) = socket.canonname {
+                    return Ok(if display.is_empty() {
+                        ai_canonname
+                    } else {
+                        format!("{ai_canonname}:{display}")
+                    });
+                }
+            }
+        } else {
+            // GNU coreutils has this behavior
+            return Ok(hostname.to_string());
+        }
+    }
+
+    Ok(host.to_string())
+}
+
+/// Run `resolve` on `host`, giving up after `timeout`
+///
+/// On timeout the lookup is left to finish in the background and `host` is
+/// returned unchanged.
+pub(crate) fn resolve_within(
+    host: &str,
+    timeout: Duration,
+    resolve: fn(&str) -> IOResult<String>,
+) -> IOResult<String> {
+    let (sender, receiver) = mpsc::channel();
+    let owned = host.to_string();
+    thread::spawn(move || {
+        // The receiver is gone if the lookup timed out
+        let _ = sender.send(resolve(&owned));
+    });
+    receiver
+        .recv_timeout(timeout)
+        .unwrap_or_else(|_| Ok(host.to_string()))
+}
+
+/// Like [`Utmpx::canon_host`] for `host`, falling back to `host` itself
+/// when the lookup takes longer than `timeout`
+pub fn canonicalize_host_within(host: &str, timeout: Duration) -> IOResult<String> {
+    resolve_within(host, timeout, canonicalize_host)
+}
+
+impl UtmpxRecord {
+    /// The record as a C `struct utmpx`, the layout of utmp and wtmp files
+    ///
+    /// Records read from systemd-logind are converted field by field, with
+    /// the fields they don't have left zeroed.
+    pub fn to_utmpx(&self) -> utmpx {
+        match self {
+            Self::Traditional(utmpx) => utmpx.inner,
+            #[cfg(feature = "feat_systemd_logind")]
+            Self::Systemd(systemd) => {
+                fn fill(field: &mut [libc::c_char], value: &str) {
+                    for (dst, src) in field.iter_mut().zip(value.bytes()) {
+                        *dst = src as libc::c_char;
+                    }
+                }
+
+                // SAFETY: utmpx is a plain C struct, for which all zeroes is valid.
+                let mut ut: utmpx = unsafe { std::mem::zeroed() };
+                ut.ut_type = systemd.record_type();
+                ut.ut_pid = systemd.pid();
+                fill(&mut ut.ut_line, &systemd.tty_device());
+                fill(&mut ut.ut_id, &systemd.terminal_suffix());
+                fill(&mut ut.ut_user, &systemd.user());
+                fill(&mut ut.ut_host, &systemd.host());
+                set_ut_tv(&mut ut, systemd.login_time().into());
+                ut
+            }
+        }
+    }
+}
+
+/// The fields of a login record, whichever backend it was read from
+///
+/// Implemented by [`Utmpx`], the systemd-logind records and [`UtmpxRecord`],
+/// so code that only reads records can be written once against
+/// [`login_records`].
+pub trait LoginRecord {
+    /// A.K.A. ut.ut_type
+    fn record_type(&self) -> i16;
+    /// A.K.A. ut.ut_type, as a [`RecordType`]
+    fn kind(&self) -> RecordType {
+        self.record_type().into()
+    }
+    /// A.K.A. ut.ut_pid
+    fn pid(&self) -> i32;
+    /// A.K.A. ut.ut_user
+    fn user(&self) -> String;
+    /// A.K.A. ut.ut_host
+    fn host(&self) -> String;
+    /// A.K.A. ut.ut_line
+    fn tty_device(&self) -> String;
+    /// A.K.A. ut.ut_tv
+    fn login_time(&self) -> time::OffsetDateTime;
+    /// check if the record is a user process
+    fn is_user_process(&self) -> bool;
+    /// Canonicalize host name using DNS
+    fn canon_host(&self) -> IOResult<String>;
+    /// Where the record was read from
+    fn source(&self) -> RecordSource;
+
+    /// Whether `other` records the same login, as told by its type, user,
+    /// line and a login time at most [`SAME_LOGIN_TOLERANCE`] apart
+    ///
+    /// utmp and systemd-logind timestamp a session separately, so the same
+    /// login rarely has the very same time in both.
+    fn matches(&self, other: &dyn LoginRecord) -> bool {
+        self.kind() == other.kind()
+            && self.user() == other.user()
+            && self.tty_device() == other.tty_device()
+            && (self.login_time() - other.login_time()).unsigned_abs() <= SAME_LOGIN_TOLERANCE
+    }
+}
+
+/// How far apart the login times of two records of the same login can be,
+/// see [`LoginRecord::matches`]
+pub const SAME_LOGIN_TOLERANCE: Duration = Duration::from_secs(2);
+
+/// `records` with one record per login, for a view merged from several
+/// backends
+///
+/// Of the records that [match](LoginRecord::matches), the one read from a
+/// utmp file is kept, in the place of the first of them: it comes from the
+/// login process itself, where systemd-logind's is a translation. Records
+/// of the same backend are all kept, however alike.
+pub fn dedup_login_records<T: LoginRecord>(records: impl IntoIterator<Item = T>) -> Vec<T> {
+    let from_utmp = |record: &T| record.source() != RecordSource::SystemdLogind;
+    let mut kept: Vec<T> = Vec::new();
+    for record in records {
+        let duplicate = kept
+            .iter()
+            .position(|other| from_utmp(other) != from_utmp(&record) && other.matches(&record));
+        match duplicate {
+            Some(i) if from_utmp(&record) => kept[i] = record,
+            Some(_) => {}
+            None => kept.push(record),
         }
     }
+    kept
+}
+
+/// Whether two reads of the login records differ in a login `who` shows
+///
+/// The order of the records is not significant, as systemd-logind lists
+/// its sessions in no particular one. Idle times are not compared either:
+/// they change with any activity on a terminal, and a watch loop should
+/// only redraw when a login comes, goes or moves.
+pub fn records_changed<T: LoginRecord>(prev: &[T], cur: &[T]) -> bool {
+    let keys = |records: &[T]| {
+        let mut keys: Vec<_> = records
+            .iter()
+            .map(|r| {
+                (
+                    r.record_type(),
+                    r.pid(),
+                    r.user(),
+                    r.tty_device(),
+                    r.host(),
+                    r.login_time(),
+                )
+            })
+            .collect();
+        keys.sort_unstable();
+        keys
+    };
+    prev.len() != cur.len() || keys(prev) != keys(cur)
+}
+
+impl<T: LoginRecord + ?Sized> LoginRecord for Box<T> {
+    fn record_type(&self) -> i16 {
+        (**self).record_type()
+    }
+    fn pid(&self) -> i32 {
+        (**self).pid()
+    }
+    fn user(&self) -> String {
+        (**self).user()
+    }
+    fn host(&self) -> String {
+        (**self).host()
+    }
+    fn tty_device(&self) -> String {
+        (**self).tty_device()
+    }
+    fn login_time(&self) -> time::OffsetDateTime {
+        (**self).login_time()
+    }
+    fn is_user_process(&self) -> bool {
+        (**self).is_user_process()
+    }
+    fn canon_host(&self) -> IOResult<String> {
+        (**self).canon_host()
+    }
+    fn source(&self) -> RecordSource {
+        (**self).source()
+    }
+}
+
+macro_rules! impl_login_record {
+    ($t:ty) => {
+        impl LoginRecord for $t {
+            fn record_type(&self) -> i16 {
+                <$t>::record_type(self)
+            }
+            fn pid(&self) -> i32 {
+                <$t>::pid(self)
+            }
+            fn user(&self) -> String {
+                <$t>::user(self)
+            }
+            fn host(&self) -> String {
+                <$t>::host(self)
+            }
+            fn tty_device(&self) -> String {
+                <$t>::tty_device(self)
+            }
+            fn login_time(&self) -> time::OffsetDateTime {
+                <$t>::login_time(self)
+            }
+            fn is_user_process(&self) -> bool {
+                <$t>::is_user_process(self)
+            }
+            fn canon_host(&self) -> IOResult<String> {
+                <$t>::canon_host(self)
+            }
+            fn source(&self) -> RecordSource {
+                <$t>::source(self)
+            }
+        }
+    };
+}
+
+impl_login_record!(Utmpx);
+impl_login_record!(UtmpxRecord);
+#[cfg(feature = "feat_systemd_logind")]
+impl_login_record!(systemd_logind::SystemdUtmpxCompat);
+
+/// All the login records of the system, read from systemd-logind when that
+/// feature is enabled and from the default utmp file otherwise
+///
+/// Unlike [`Utmpx::iter_all_records`], failing to reach systemd-logind is
+/// reported instead of yielding no records.
+// The error only comes from systemd-logind
+#[cfg_attr(not(feature = "feat_systemd_logind"), allow(clippy::unnecessary_wraps))]
+pub fn login_records() -> UResult<Box<dyn Iterator<Item = Box<dyn LoginRecord>>>> {
+    #[cfg(feature = "feat_systemd_logind")]
+    {
+        let records = systemd_logind::SystemdUtmpxIter::new()?;
+        Ok(Box::new(
+            records.map(|record| Box::new(record) as Box<dyn LoginRecord>),
+        ))
+    }
+
+    #[cfg(not(feature = "feat_systemd_logind"))]
+    {
+        Ok(Box::new(
+            Utmpx::iter_all_records().map(|record| Box::new(record) as Box<dyn LoginRecord>),
+        ))
+    }
+}
+
+/// The login records of both the default utmp file and systemd-logind, with
+/// one record per login, see [`dedup_login_records`]
+///
+/// Nothing reads both by default. Without the systemd-logind feature, these
+/// are the records of the utmp file alone.
+#[cfg_attr(not(feature = "feat_systemd_logind"), allow(clippy::unnecessary_wraps))]
+pub fn merged_login_records() -> UResult<Vec<Box<dyn LoginRecord>>> {
+    let utmp = Utmpx::iter_file_records(Path::new(DEFAULT_FILE))
+        .map(|record| Box::new(record) as Box<dyn LoginRecord>);
+
+    #[cfg(feature = "feat_systemd_logind")]
+    {
+        // Read before systemd-logind, while the utmp file is the one set.
+        let utmp: Vec<_> = utmp.collect();
+        let logind = systemd_logind::SystemdUtmpxIter::new()?
+            .map(|record| Box::new(record) as Box<dyn LoginRecord>);
+        Ok(dedup_login_records(utmp.into_iter().chain(logind)))
+    }
+
+    #[cfg(not(feature = "feat_systemd_logind"))]
+    {
+        Ok(utmp.collect())
+    }
+}
+
+impl UtmpxRecord {
+    /// A `BOOT_TIME` record for a boot at `time`, learned from `source`,
+    /// for when the login records have none
+    pub fn boot(time: SystemTime, source: RecordSource) -> Self {
+        // SAFETY: utmpx is a plain C struct, for which all zeroes is valid.
+        let mut ut: utmpx = unsafe { std::mem::zeroed() };
+        set_ut_type_user(&mut ut, BOOT_TIME, b"reboot");
+        ut.ut_line[0] = b'~' as libc::c_char;
+        set_ut_tv(&mut ut, time);
+        Self::Traditional(Box::new(Utmpx { inner: ut, source }))
+    }
+}
+
+/// Set `ut.ut_tv` to `time`, truncated to microseconds
+///
+/// `tv_sec` only has 32 bits on some platforms, glibc's on x86_64 among
+/// them, so times past 2038 saturate rather than wrap around. Times before
+/// the epoch are stored as the epoch.
+pub(crate) fn set_ut_tv(ut: &mut utmpx, time: SystemTime) {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = i64::try_from(since_epoch.as_secs()).unwrap_or(i64::MAX);
+    ut.ut_tv.tv_sec = secs.try_into().unwrap_or(i32::MAX as _);
+    ut.ut_tv.tv_usec = since_epoch.subsec_micros() as _;
+}
+
+/// The boot time given by the `btime` line of `/proc/stat`
+pub(crate) fn parse_btime(content: &str) -> Option<SystemTime> {
+    let secs = content
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// The boot time in the `/proc/stat`-formatted file at `path`
+fn proc_stat_boot_time(path: &Path) -> Option<SystemTime> {
+    parse_btime(&fs::read_to_string(path).ok()?)
+}
+
+/// The boot time in `kern.boottime`, which macOS always has even when its
+/// utmpx lacks a `BOOT_TIME` record
+///
+/// The output of `sysctl` is parsed rather than calling `sysctl(3)`, to
+/// stay free of unsafe code. It looks like
+/// `{ sec = 1729338352, usec = 0 } Wed Oct 19 08:25:52 2025`.
+#[cfg(target_os = "macos")]
+fn sysctl_boot_time() -> Option<SystemTime> {
+    let output = std::process::Command::new("sysctl")
+        .args(["-n", "kern.boottime"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (_, rest) = stdout.split_once("sec = ")?;
+    let (secs, _) = rest.split_once(',')?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs.trim().parse().ok()?))
+}
+
+/// When the system booted
+///
+/// This is the first of the `BOOT_TIME` login record, systemd's startup
+/// timestamps, the kernel's `btime` in `/proc/stat` and, on macOS,
+/// `kern.boottime` that is available, so it is known even in containers
+/// without any login records.
+pub fn boot_time() -> UResult<SystemTime> {
+    boot_record().map(|record| SystemTime::from(record.login_time()))
+}
+
+/// A `BOOT_TIME` record for [`boot_time`], with the source it came from
+pub fn boot_record() -> UResult<UtmpxRecord> {
+    // A record without a time doesn't know when the system booted
+    let from_records = || {
+        Utmpx::iter_all_records().find(|record| {
+            record.kind() == RecordType::BootTime && record.login_time().unix_timestamp() > 0
+        })
+    };
+    #[cfg(feature = "feat_systemd_logind")]
+    let from_logind = || {
+        systemd_logind::read_boot_timestamp()
+            .ok()
+            .map(|time| UtmpxRecord::boot(time, RecordSource::SystemdLogind))
+    };
+    #[cfg(not(feature = "feat_systemd_logind"))]
+    let from_logind = || None;
+    #[cfg(target_os = "macos")]
+    let from_sysctl =
+        || sysctl_boot_time().map(|time| UtmpxRecord::boot(time, RecordSource::Sysctl));
+    #[cfg(not(target_os = "macos"))]
+    let from_sysctl = || None;
+
+    from_records()
+        .or_else(from_logind)
+        .or_else(|| {
+            proc_stat_boot_time(Path::new("/proc/stat"))
+                .map(|time| UtmpxRecord::boot(time, RecordSource::Proc))
+        })
+        .or_else(from_sysctl)
+        .ok_or_else(|| USimpleError::new(1, "cannot determine the boot time"))
+}
+
+/// Write `record` to `w` in the platform's binary utmpx layout, as read back
+/// by [`Utmpx::iter_all_records_from`]
+pub fn write_record<W: Write>(w: &mut W, record: &UtmpxRecord) -> IOResult<()> {
+    let ut = record.to_utmpx();
+    // SAFETY: `ut` is a plain C struct that lives for the whole borrow.
+    let bytes =
+        unsafe { std::slice::from_raw_parts((&raw const ut).cast::<u8>(), size_of::<utmpx>()) };
+    w.write_all(bytes)
+}
+
+impl UtmpxIter {
+    /// Only the records of processes that have exited, see
+    /// [`UtmpxRecord::is_dead_process`]
+    pub fn dead_processes(self) -> impl Iterator<Item = UtmpxRecord> {
+        self.filter(UtmpxRecord::is_dead_process)
+    }
 }
 
 impl Iterator for UtmpxIter {
@@ -574,6 +1273,7 @@ impl Iterator for UtmpxIter {
                 // makes things easier.
                 Some(UtmpxRecord::Traditional(Box::new(Utmpx {
                     inner: ptr::read(res.cast_const()),
+                    source: self.source,
                 })))
             }
         }
@@ -588,3 +1288,433 @@ impl Drop for UtmpxIter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_resolver(host: &str) -> IOResult<String> {
+        if host.is_empty() {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+        Ok(format!("{host}.example.org"))
+    }
+
+    fn slow_resolver(host: &str) -> IOResult<String> {
+        thread::sleep(Duration::from_secs(5));
+        fast_resolver(host)
+    }
+
+    #[test]
+    fn test_resolve_within_times_out_to_raw_host() {
+        let host = resolve_within("box:0", Duration::from_millis(50), slow_resolver).unwrap();
+        assert_eq!(host, "box:0");
+    }
+
+    #[test]
+    fn test_resolve_within_returns_lookup() {
+        let host = resolve_within("box", Duration::from_secs(5), fast_resolver).unwrap();
+        assert_eq!(host, "box.example.org");
+    }
+
+    fn ut_tv_of(time: SystemTime) -> (i64, i64) {
+        // SAFETY: utmpx is a plain C struct, for which all zeroes is valid.
+        let mut ut: utmpx = unsafe { std::mem::zeroed() };
+        set_ut_tv(&mut ut, time);
+        (ut.ut_tv.tv_sec as i64, ut.ut_tv.tv_usec as i64)
+    }
+
+    #[test]
+    fn test_set_ut_tv() {
+        assert_eq!(ut_tv_of(UNIX_EPOCH), (0, 0));
+        assert_eq!(
+            ut_tv_of(UNIX_EPOCH + Duration::new(1_716_371_283, 123_456_789)),
+            (1_716_371_283, 123_456)
+        );
+        assert_eq!(ut_tv_of(UNIX_EPOCH - Duration::from_secs(1)), (0, 0));
+    }
+
+    #[test]
+    fn test_set_ut_tv_near_32_bit_overflow() {
+        let last = UNIX_EPOCH + Duration::from_secs(i32::MAX as u64);
+        assert_eq!(ut_tv_of(last), (i32::MAX as i64, 0));
+
+        // SAFETY: utmpx is a plain C struct, for which all zeroes is valid.
+        let ut: utmpx = unsafe { std::mem::zeroed() };
+        let past = if size_of_val(&ut.ut_tv.tv_sec) == 4 {
+            i32::MAX as i64
+        } else {
+            i32::MAX as i64 + 1
+        };
+        assert_eq!(ut_tv_of(last + Duration::from_secs(1)), (past, 0));
+    }
+
+    #[test]
+    fn test_proc_stat_boot_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let stat = dir.path().join("stat");
+        fs::write(
+            &stat,
+            "cpu  1 2 3 4\nintr 5\nctxt 6\nbtime 1716370000\nprocesses 7\n",
+        )
+        .unwrap();
+        assert_eq!(
+            proc_stat_boot_time(&stat),
+            Some(UNIX_EPOCH + Duration::from_secs(1_716_370_000))
+        );
+
+        fs::write(&stat, "cpu  1 2 3 4\nbtime soon\n").unwrap();
+        assert_eq!(proc_stat_boot_time(&stat), None);
+        assert_eq!(proc_stat_boot_time(&dir.path().join("none")), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn test_sysctl_boot_time() {
+        let boot_time = sysctl_boot_time().expect("kern.boottime should be readable");
+        // After 2000-01-01 and before now
+        assert!(boot_time > UNIX_EPOCH + Duration::from_secs(946_684_800));
+        assert!(boot_time < SystemTime::now());
+    }
+
+    #[test]
+    fn test_boot_record() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_716_370_000);
+        let record = UtmpxRecord::boot(time, RecordSource::Proc);
+        assert_eq!(record.kind(), RecordType::BootTime);
+        assert_eq!(record.source(), RecordSource::Proc);
+        assert_eq!(record.user(), "reboot");
+        assert_eq!(record.tty_device(), "~");
+        assert_eq!(SystemTime::from(record.login_time()), time);
+    }
+
+    #[test]
+    fn test_records_from_file_have_file_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wtmp");
+        let mut file = File::create(&path).unwrap();
+        let time = UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+        write_record(&mut file, &UtmpxRecord::boot(time, RecordSource::Proc)).unwrap();
+        drop(file);
+
+        let records: Vec<_> = Utmpx::iter_file_records(&path).collect();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].kind(), RecordType::BootTime);
+        assert_eq!(records[0].source(), RecordSource::File);
+    }
+
+    /// A record of `user` on `line` at `secs`, read from `source`
+    struct MockRecord {
+        user: &'static str,
+        line: &'static str,
+        secs: i64,
+        source: RecordSource,
+    }
+
+    impl LoginRecord for MockRecord {
+        fn record_type(&self) -> i16 {
+            RecordType::UserProcess.into()
+        }
+        fn pid(&self) -> i32 {
+            0
+        }
+        fn user(&self) -> String {
+            self.user.to_string()
+        }
+        fn host(&self) -> String {
+            String::new()
+        }
+        fn tty_device(&self) -> String {
+            self.line.to_string()
+        }
+        fn login_time(&self) -> time::OffsetDateTime {
+            time::OffsetDateTime::from_unix_timestamp(self.secs).unwrap()
+        }
+        fn is_user_process(&self) -> bool {
+            true
+        }
+        fn canon_host(&self) -> IOResult<String> {
+            Ok(String::new())
+        }
+        fn source(&self) -> RecordSource {
+            self.source
+        }
+    }
+
+    #[test]
+    fn test_record_type_conversions() {
+        for record_type in [
+            RecordType::BootTime,
+            #[cfg(not(target_os = "freebsd"))]
+            RecordType::RunLevel,
+            RecordType::NewTime,
+            RecordType::OldTime,
+            RecordType::InitProcess,
+            RecordType::LoginProcess,
+            RecordType::UserProcess,
+            RecordType::DeadProcess,
+        ] {
+            assert_eq!(RecordType::from(i16::from(record_type)), record_type);
+        }
+        assert_eq!(RecordType::from(USER_PROCESS), RecordType::UserProcess);
+        assert_eq!(RecordType::from(BOOT_TIME), RecordType::BootTime);
+        // EMPTY
+        assert_eq!(RecordType::from(0), RecordType::Other(0));
+        assert_eq!(i16::from(RecordType::Other(0)), 0);
+    }
+
+    #[test]
+    fn test_dedup_login_records() {
+        let record = |user, line, secs, source| MockRecord {
+            user,
+            line,
+            secs,
+            source,
+        };
+        let utmp = RecordSource::Utmp;
+        let logind = RecordSource::SystemdLogind;
+        let records = dedup_login_records([
+            record("alice", "pts/0", 1_500_000_001, logind),
+            record("bob", "tty1", 1_500_000_000, logind),
+            record("alice", "pts/0", 1_500_000_000, utmp),
+            // Too far apart, on another line, or of the same backend
+            record("bob", "tty1", 1_500_000_003, utmp),
+            record("bob", "tty2", 1_500_000_000, utmp),
+            record("bob", "tty1", 1_500_000_000, logind),
+        ]);
+        let kept: Vec<_> = records
+            .iter()
+            .map(|r| (r.user, r.line, r.secs, r.source))
+            .collect();
+        assert_eq!(
+            kept,
+            [
+                ("alice", "pts/0", 1_500_000_000, utmp),
+                ("bob", "tty1", 1_500_000_000, logind),
+                ("bob", "tty1", 1_500_000_003, utmp),
+                ("bob", "tty2", 1_500_000_000, utmp),
+                ("bob", "tty1", 1_500_000_000, logind),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_records_changed() {
+        let read = |records: &[(&'static str, &'static str, i64)]| -> Vec<MockRecord> {
+            records
+                .iter()
+                .map(|&(user, line, secs)| MockRecord {
+                    user,
+                    line,
+                    secs,
+                    source: RecordSource::SystemdLogind,
+                })
+                .collect()
+        };
+        let prev = read(&[
+            ("alice", "pts/0", 1_500_000_000),
+            ("bob", "tty2", 1_500_000_000),
+        ]);
+        assert!(!records_changed(&prev, &prev));
+
+        // Same records in another order
+        let reordered = read(&[
+            ("bob", "tty2", 1_500_000_000),
+            ("alice", "pts/0", 1_500_000_000),
+        ]);
+        assert!(!records_changed(&prev, &reordered));
+
+        // A login was added
+        let added = read(&[
+            ("alice", "pts/0", 1_500_000_000),
+            ("bob", "tty2", 1_500_000_000),
+            ("alice", "pts/1", 1_500_000_060),
+        ]);
+        assert!(records_changed(&prev, &added));
+
+        // A login was removed
+        let removed = read(&[("bob", "tty2", 1_500_000_000)]);
+        assert!(records_changed(&prev, &removed));
+
+        // A login moved to another terminal, or logged in again
+        let moved = read(&[
+            ("alice", "pts/3", 1_500_000_000),
+            ("bob", "tty2", 1_500_000_000),
+        ]);
+        assert!(records_changed(&prev, &moved));
+        let again = read(&[
+            ("alice", "pts/0", 1_500_000_060),
+            ("bob", "tty2", 1_500_000_000),
+        ]);
+        assert!(records_changed(&prev, &again));
+    }
+
+    #[test]
+    fn test_dedup_boxed_records() {
+        let boxed = |user, line, secs, source| {
+            Box::new(MockRecord {
+                user,
+                line,
+                secs,
+                source,
+            }) as Box<dyn LoginRecord>
+        };
+        let utmp = RecordSource::Utmp;
+        let logind = RecordSource::SystemdLogind;
+        let kept = |records: Vec<Box<dyn LoginRecord>>| -> Vec<_> {
+            records
+                .iter()
+                .map(|r| (r.user(), r.tty_device(), r.source()))
+                .collect()
+        };
+
+        // Overlapping: the utmp record stands for both
+        let records = dedup_login_records([
+            boxed("alice", "pts/0", 1_500_000_000, utmp),
+            boxed("bob", "tty1", 1_500_000_000, utmp),
+            boxed("alice", "pts/0", 1_500_000_001, logind),
+            boxed("bob", "tty1", 1_500_000_000, logind),
+        ]);
+        assert_eq!(
+            kept(records),
+            [
+                ("alice".to_string(), "pts/0".to_string(), utmp),
+                ("bob".to_string(), "tty1".to_string(), utmp),
+            ]
+        );
+
+        // Disjoint: all of them are kept
+        let records = dedup_login_records([
+            boxed("alice", "pts/0", 1_500_000_000, utmp),
+            boxed("bob", "tty1", 1_500_000_000, logind),
+        ]);
+        assert_eq!(
+            kept(records),
+            [
+                ("alice".to_string(), "pts/0".to_string(), utmp),
+                ("bob".to_string(), "tty1".to_string(), logind),
+            ]
+        );
+    }
+
+    /// A `USER_PROCESS` record of `user` on `line` with `pid`
+    fn user_process(user: &[u8], line: &[u8], pid: i32) -> Utmpx {
+        // SAFETY: utmpx is a plain C struct, for which all zeroes is valid.
+        let mut inner: utmpx = unsafe { std::mem::zeroed() };
+        set_ut_type_user(&mut inner, USER_PROCESS, user);
+        inner.ut_pid = pid;
+        for (dst, src) in inner.ut_line.iter_mut().zip(line) {
+            *dst = *src as libc::c_char;
+        }
+        Utmpx {
+            inner,
+            source: RecordSource::File,
+        }
+    }
+
+    /// The user, line and pid of the records in the file at `path`
+    fn file_records(path: &Path) -> Vec<(String, String, i32)> {
+        Utmpx::iter_from_file(path)
+            .unwrap()
+            .map(|r| (r.user(), r.tty_device(), r.pid()))
+            .collect()
+    }
+
+    fn record(user: &str, line: &str, pid: i32) -> (String, String, i32) {
+        (user.to_string(), line.to_string(), pid)
+    }
+
+    #[test]
+    fn test_iter_from_file_ignores_partial_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wtmp");
+        let mut bytes = user_process(b"alice", b"pts/0", 1).as_bytes().to_vec();
+        bytes.extend(user_process(b"bob", b"tty1", 2).as_bytes());
+        let third = user_process(b"carol", b"tty2", 3);
+        bytes.extend(&third.as_bytes()[..size_of::<utmpx>() / 2]);
+        fs::write(&path, bytes).unwrap();
+
+        assert_eq!(
+            file_records(&path),
+            [record("alice", "pts/0", 1), record("bob", "tty1", 2)]
+        );
+        assert!(
+            Utmpx::iter_from_file(&path)
+                .unwrap()
+                .all(|r| r.source() == RecordSource::File)
+        );
+    }
+
+    #[test]
+    fn test_put_utline_appends() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wtmp");
+        put_utline(&user_process(b"alice", b"pts/0", 1), &path).unwrap();
+        put_utline(&user_process(b"alice", b"pts/0", 2), &path).unwrap();
+        assert_eq!(
+            file_records(&path),
+            [record("alice", "pts/0", 1), record("alice", "pts/0", 2)]
+        );
+    }
+
+    #[test]
+    fn test_update_utline_replaces_same_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("utmp");
+        update_utline(&user_process(b"alice", b"pts/0", 1), &path).unwrap();
+        update_utline(&user_process(b"bob", b"tty1", 2), &path).unwrap();
+        update_utline(&user_process(b"carol", b"pts/0", 3), &path).unwrap();
+        assert_eq!(
+            file_records(&path),
+            [record("carol", "pts/0", 3), record("bob", "tty1", 2)]
+        );
+
+        // A new line overwrites a partial record at the end.
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.extend(&user_process(b"dave", b"tty2", 4).as_bytes()[..10]);
+        fs::write(&path, bytes).unwrap();
+        update_utline(&user_process(b"erin", b"tty3", 5), &path).unwrap();
+        assert_eq!(
+            file_records(&path),
+            [
+                record("carol", "pts/0", 3),
+                record("bob", "tty1", 2),
+                record("erin", "tty3", 5)
+            ]
+        );
+        assert_eq!(
+            fs::metadata(&path).unwrap().len(),
+            3 * size_of::<utmpx>() as u64
+        );
+    }
+
+    #[test]
+    fn test_iter_from_file_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(Utmpx::iter_from_file(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_login_record_matches_utmpx() {
+        // SAFETY: utmpx is a plain C struct, for which all zeroes is valid.
+        let mut inner: utmpx = unsafe { std::mem::zeroed() };
+        set_ut_type_user(&mut inner, USER_PROCESS, b"alice");
+        inner.ut_pid = 42;
+        for (dst, src) in inner.ut_line.iter_mut().zip(b"pts/3") {
+            *dst = *src as libc::c_char;
+        }
+        let record = UtmpxRecord::Traditional(Box::new(Utmpx {
+            inner,
+            source: RecordSource::File,
+        }));
+
+        let login: &dyn LoginRecord = &record;
+        assert_eq!(login.kind(), RecordType::UserProcess);
+        assert_eq!(login.pid(), 42);
+        assert_eq!(login.user(), "alice");
+        assert_eq!(login.tty_device(), "pts/3");
+        assert_eq!(login.host(), "");
+        assert!(login.is_user_process());
+        assert_eq!(login.source(), RecordSource::File);
+    }
+}
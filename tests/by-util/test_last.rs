@@ -0,0 +1,114 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+// spell-checker:ignore (paths) wtmp
+
+use uutests::new_ucmd;
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+use uutests::util::{TestScenario, utmp_record};
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+use uutests::util_name;
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+const BOOT_TIME: i16 = 2;
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+const USER_PROCESS: i16 = 7;
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+const DEAD_PROCESS: i16 = 8;
+
+/// Two boots: alice logs out, bob is left logged in when the system
+/// crashes, and after the second boot carol is still logged in while dave
+/// logs out after a day.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn wtmp() -> Vec<u8> {
+    let boot = 1_500_000_000;
+    [
+        utmp_record(BOOT_TIME, 1, "~", "reboot", "6.1.0", boot),
+        utmp_record(USER_PROCESS, 1, "pts/0", "alice", "example.com", boot + 60),
+        utmp_record(USER_PROCESS, 1, "pts/1", "bob", "", boot + 120),
+        utmp_record(DEAD_PROCESS, 1, "pts/0", "", "", boot + 1560),
+        utmp_record(BOOT_TIME, 1, "~", "reboot", "6.1.0", boot + 7200),
+        utmp_record(USER_PROCESS, 1, "pts/0", "carol", "", boot + 7260),
+        utmp_record(USER_PROCESS, 1, "pts/2", "dave", "", boot + 7320),
+        utmp_record(DEAD_PROCESS, 1, "pts/2", "", "", boot + 7320 + 90_000),
+    ]
+    .concat()
+}
+
+#[test]
+fn test_invalid_arg() {
+    new_ucmd!().arg("--definitely-invalid").fails_with_code(1);
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_sessions() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write_bytes("wtmp", &wtmp());
+
+    ts.ucmd()
+        .env("TZ", "UTC")
+        .args(&["-f", "wtmp"])
+        .succeeds()
+        .stdout_is(concat!(
+            "dave     pts/2                         Fri Jul 14 04:42 - 05:42  (1+01:00)\n",
+            "carol    pts/0                         Fri Jul 14 04:41   still logged in\n",
+            "reboot   system boot  6.1.0            Fri Jul 14 04:40   still running\n",
+            "bob      pts/1                         Fri Jul 14 02:42 - crash  (01:58)\n",
+            "alice    pts/0        example.com      Fri Jul 14 02:41 - 03:06  (00:25)\n",
+            "reboot   system boot  6.1.0            Fri Jul 14 02:40 - 04:40  (02:00)\n",
+            "\n",
+            "wtmp begins Fri Jul 14 02:40:00 2017\n",
+        ));
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_limit_and_full_times() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write_bytes("wtmp", &wtmp());
+
+    ts.ucmd()
+        .env("TZ", "UTC")
+        .args(&["-F", "-n", "2", "--file=wtmp"])
+        .succeeds()
+        .stdout_is(concat!(
+            "dave     pts/2                         Fri Jul 14 04:42:00 2017 - Sat Jul 15 05:42:00 2017  (1+01:00)\n",
+            "carol    pts/0                         Fri Jul 14 04:41:00 2017   still logged in\n",
+            "\n",
+            "wtmp begins Fri Jul 14 02:40:00 2017\n",
+        ));
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_login_without_logout_on_reused_line() {
+    let ts = TestScenario::new(util_name!());
+    let records = [
+        utmp_record(USER_PROCESS, 1, "pts/0", "alice", "", 1_500_000_000),
+        utmp_record(USER_PROCESS, 1, "pts/0", "bob", "", 1_500_000_060),
+    ]
+    .concat();
+    ts.fixtures.write_bytes("wtmp", &records);
+
+    ts.ucmd()
+        .env("TZ", "UTC")
+        .args(&["-f", "wtmp"])
+        .succeeds()
+        .stdout_is(concat!(
+            "bob      pts/0                         Fri Jul 14 02:41   still logged in\n",
+            "alice    pts/0                         Fri Jul 14 02:40    gone - no logout\n",
+            "\n",
+            "wtmp begins Fri Jul 14 02:40:00 2017\n",
+        ));
+}
+
+#[test]
+fn test_missing_file() {
+    new_ucmd!()
+        .args(&["-f", "missing"])
+        .fails_with_code(1)
+        .stderr_is("last: cannot open missing: No such file or directory\n");
+}
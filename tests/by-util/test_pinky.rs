@@ -81,6 +81,36 @@ fn test_short_format_i() {
     assert_eq!(v_actual, v_expect);
 }
 
+#[cfg(unix)]
+#[test]
+#[cfg(not(target_os = "openbsd"))]
+fn test_short_format_columns() {
+    // allow whitespace variation, as for -i and -q
+    let ts = TestScenario::new(util_name!());
+    for args in [&["-s"][..], &["-f"], &["-w"], &["-s", "-w", "-f"]] {
+        let actual = ts.ucmd().args(args).succeeds().stdout_move_str();
+        let expect = unwrap_or_return!(expected_result(&ts, args)).stdout_move_str();
+        let v_actual: Vec<&str> = actual.split_whitespace().collect();
+        let v_expect: Vec<&str> = expect.split_whitespace().collect();
+        assert_eq!(v_actual, v_expect, "{args:?}");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+#[cfg(not(target_os = "openbsd"))]
+fn test_long_format_omissions() {
+    let ts = TestScenario::new(util_name!());
+    for flags in ["-lb", "-lh", "-lp", "-lbhp"] {
+        let args = [flags, "root"];
+        let expect = unwrap_or_return!(expected_result(&ts, &args));
+        ts.ucmd()
+            .args(&args)
+            .succeeds()
+            .stdout_is(expect.stdout_str());
+    }
+}
+
 #[cfg(unix)]
 #[test]
 #[cfg(not(target_os = "openbsd"))]
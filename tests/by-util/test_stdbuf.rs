@@ -131,6 +131,66 @@ fn test_no_such() {
         .stderr_contains("No such file or directory");
 }
 
+// libstdbuf goes to a private directory under TMPDIR, never the working
+// directory, and is removed again when the command can't be run...
+#[cfg(all(target_os = "linux", not(feature = "feat_external_libstdbuf")))]
+#[test]
+fn test_stdbuf_leaves_no_library_behind() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir("tmp");
+    ucmd.env("TMPDIR", at.plus("tmp"))
+        .args(&["-oL", "no_such"])
+        .fails_with_code(127);
+    assert_eq!(std::fs::read_dir(at.plus("tmp")).unwrap().count(), 0);
+    let entries: Vec<_> = std::fs::read_dir(at.as_string()).unwrap().collect();
+    assert_eq!(entries.len(), 1);
+}
+
+// ...or once it has exited. A helper process removes it then, so it may
+// take a moment.
+#[cfg(all(target_os = "linux", not(feature = "feat_external_libstdbuf")))]
+#[test]
+fn test_stdbuf_removes_library_after_command() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir("tmp");
+    ucmd.env("TMPDIR", at.plus("tmp"))
+        .args(&["-oL", "true"])
+        .succeeds();
+    wait_for_empty_dir(&at.plus("tmp"));
+}
+
+// The same whether the command fails or is killed by a signal.
+#[cfg(all(target_os = "linux", not(feature = "feat_external_libstdbuf")))]
+#[test]
+fn test_stdbuf_removes_library_after_failed_command() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir("tmp");
+    ucmd.env("TMPDIR", at.plus("tmp"))
+        .args(&["-oL", "sh", "-c", "exit 3"])
+        .fails_with_code(3);
+    wait_for_empty_dir(&at.plus("tmp"));
+
+    new_ucmd!()
+        .env("TMPDIR", at.plus("tmp"))
+        .args(&["-oL", "sh", "-c", "kill -9 $$"])
+        .fails();
+    wait_for_empty_dir(&at.plus("tmp"));
+}
+
+/// Wait up to 5 seconds for `dir` to become empty.
+#[cfg(all(target_os = "linux", not(feature = "feat_external_libstdbuf")))]
+fn wait_for_empty_dir(dir: &std::path::Path) {
+    let start = std::time::Instant::now();
+    while std::fs::read_dir(dir).unwrap().count() > 0 {
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(5),
+            "libstdbuf was left in {}",
+            dir.display()
+        );
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+}
+
 // Disabled on x86_64-unknown-linux-musl because the cross-rs Docker image for this target
 // does not provide musl-compiled system utilities (like head), leading to dynamic linker errors
 // when preloading musl-compiled libstdbuf.so into glibc-compiled binaries. Same thing for FreeBSD.
@@ -214,6 +274,78 @@ fn test_stdbuf_trailing_var_arg() {
         .stdout_is("jumps over the lazy dog.");
 }
 
+// Disabled on x86_64-unknown-linux-musl because the cross-rs Docker image for this target
+// does not provide musl-compiled system utilities (like printf), leading to dynamic linker errors
+// when preloading musl-compiled libstdbuf.so into glibc-compiled binaries. Same thing for FreeBSD.
+#[cfg(all(
+    not(target_os = "windows"),
+    not(target_os = "freebsd"),
+    not(target_os = "openbsd"),
+    not(all(target_arch = "x86_64", target_env = "musl"))
+))]
+#[test]
+fn test_stdbuf_arguments_verbatim() {
+    new_ucmd!()
+        .args(&[
+            "-oL",
+            "printf",
+            "[%s]\\n",
+            "a b",
+            "\"quoted\"",
+            "it's",
+            "$HOME",
+            "*",
+            "",
+        ])
+        .succeeds()
+        .stdout_is("[a b]\n[\"quoted\"]\n[it's]\n[$HOME]\n[*]\n[]\n");
+}
+
+// Disabled on x86_64-unknown-linux-musl because the cross-rs Docker image for this target
+// does not provide musl-compiled system utilities (like cat), leading to dynamic linker errors
+// when preloading musl-compiled libstdbuf.so into glibc-compiled binaries. Same thing for FreeBSD.
+#[cfg(all(
+    not(target_os = "windows"),
+    not(target_os = "freebsd"),
+    not(target_os = "openbsd"),
+    not(all(target_arch = "x86_64", target_env = "musl"))
+))]
+#[test]
+fn test_stdbuf_line_buffered_cat() {
+    new_ucmd!()
+        .args(&["-oL", "cat"])
+        .pipe_in("first line\nsecond line\n")
+        .succeeds()
+        .stdout_is("first line\nsecond line\n");
+}
+
+#[cfg(all(
+    not(target_os = "windows"),
+    not(target_os = "freebsd"),
+    not(target_os = "openbsd"),
+    not(all(target_arch = "x86_64", target_env = "musl"))
+))]
+#[test]
+fn test_stdbuf_exit_status_of_command() {
+    new_ucmd!()
+        .args(&["-oL", "sh", "-c", "exit 3"])
+        .fails_with_code(3)
+        .no_output();
+}
+
+// The command keeps the libraries already preloaded, after libstdbuf.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_stdbuf_keeps_ld_preload() {
+    let result = new_ucmd!()
+        .env("LD_PRELOAD", "libc.so.6")
+        .args(&["-oL", "sh", "-c", "printf %s \"$LD_PRELOAD\""])
+        .succeeds();
+    let preload = result.stdout_str();
+    assert!(preload.ends_with(":libc.so.6"), "{preload}");
+    assert!(preload.contains("libstdbuf"), "{preload}");
+}
+
 #[cfg(not(target_os = "windows"))]
 #[test]
 fn test_stdbuf_line_buffering_stdin_fails() {
@@ -303,6 +303,23 @@ fn test_uptime_since() {
     new_ucmd!().arg("--since").succeeds().stdout_matches(&re);
 }
 
+#[test]
+fn test_uptime_since_is_past_boot_time() {
+    use jiff::{Timestamp, civil::DateTime, tz::TimeZone};
+
+    let result = new_ucmd!().env("TZ", "UTC").arg("-s").succeeds();
+    // Only the boot time, without the usual line
+    let since = result.stdout_str().strip_suffix('\n').unwrap();
+    assert!(!since.contains('\n'), "{since:?}");
+    let boot_time = DateTime::strptime("%Y-%m-%d %H:%M:%S", since)
+        .unwrap()
+        .to_zoned(TimeZone::UTC)
+        .unwrap()
+        .timestamp();
+    assert!(boot_time < Timestamp::now());
+    assert!(boot_time > Timestamp::UNIX_EPOCH);
+}
+
 #[test]
 fn test_uptime_pretty_print() {
     new_ucmd!()
@@ -3,9 +3,14 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 use uutests::new_ucmd;
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+use uutests::util::utmp_record;
 #[cfg(any(target_vendor = "apple", target_os = "linux"))]
 use uutests::{util::TestScenario, util_name};
 
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+const USER_PROCESS: i16 = 7;
+
 #[ignore = "does not work as same as users > /dev/full"]
 #[test]
 #[cfg(target_os = "linux")]
@@ -57,3 +62,28 @@ fn test_users_check_name_openbsd() {
         .succeeds()
         .stdout_contains("test");
 }
+
+#[test]
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn test_users_empty_file() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write_bytes("utmp", &[]);
+    ts.ucmd().arg("utmp").succeeds().no_output();
+}
+
+#[test]
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn test_users_file_sessions() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(USER_PROCESS, 1, "pts/2", "zed", "", 0);
+    records.extend(utmp_record(USER_PROCESS, 1, "pts/1", "alice", "", 0));
+    records.extend(utmp_record(USER_PROCESS, 1, "tty1", "bob", "", 0));
+    records.extend(utmp_record(USER_PROCESS, 1, "pts/3", "alice", "", 0));
+    ts.fixtures.write_bytes("utmp", &records);
+
+    // One name per session, sorted
+    ts.ucmd()
+        .arg("utmp")
+        .succeeds()
+        .stdout_is("alice alice bob zed\n");
+}
@@ -3,20 +3,69 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
-// spell-checker:ignore (flags) runlevel mesg
+// spell-checker:ignore (flags) runlevel mesg testusr
 
+#[cfg(unix)]
+use uutests::at_and_ucmd;
 use uutests::new_ucmd;
 use uutests::unwrap_or_return;
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+use uutests::util::utmp_record;
 use uutests::util::{TestScenario, expected_result, gnu_cmd_result};
 use uutests::util_name;
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+const BOOT_TIME: i16 = 2;
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+const USER_PROCESS: i16 = 7;
+
 #[test]
 fn test_invalid_arg() {
     new_ucmd!().arg("--definitely-invalid").fails_with_code(1);
 }
 
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_gnu_abbreviations() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write_bytes(
+        "utmp",
+        &utmp_record(USER_PROCESS, 1, "pts/1", "testusr", "", 1_716_371_283),
+    );
+
+    for (abbreviation, option) in [
+        ("--b", "--boot"),
+        ("--c", "--count"),
+        ("--co", "--count"),
+        ("--cou", "--count"),
+        ("--coun", "--count"),
+        ("--d", "--dead"),
+        ("--de", "--dead"),
+        ("--hea", "--heading"),
+        ("--head", "--heading"),
+        ("--m", "--mesg"),
+        ("--r", "--runlevel"),
+        ("--s", "--short"),
+        ("--t", "--time"),
+        ("--ti", "--time"),
+        ("--tim", "--time"),
+        ("--u", "--users"),
+        ("--w", "--writable"),
+    ] {
+        let expected = ts
+            .ucmd()
+            .args(&[option, "utmp"])
+            .succeeds()
+            .stdout_move_str();
+        ts.ucmd()
+            .args(&[abbreviation, "utmp"])
+            .succeeds()
+            .stdout_is(expected);
+    }
+}
+
 #[cfg(unix)]
 #[test]
-#[ignore = "issue #3219"]
 fn test_count() {
     let ts = TestScenario::new(util_name!());
     for opt in ["-q", "--count", "--c"] {
@@ -40,9 +89,19 @@ fn test_boot() {
     }
 }
 
+#[cfg(target_os = "linux")]
+#[test]
+fn test_boot_without_boot_record() {
+    // Even without a boot record, e.g. in a container, the kernel knows
+    // when it booted.
+    new_ucmd!()
+        .arg("-b")
+        .succeeds()
+        .stdout_matches(&regex::Regex::new(r"^ +system boot +\S").unwrap());
+}
+
 #[cfg(unix)]
 #[test]
-#[ignore = "issue #3219"]
 fn test_heading() {
     let ts = TestScenario::new(util_name!());
     for opt in ["-H", "--heading", "--head"] {
@@ -59,9 +118,50 @@ fn test_heading() {
     }
 }
 
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_heading_aligned() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_716_371_201);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        4242,
+        "pts/1",
+        "testusr",
+        "example.com",
+        1_716_371_283,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+
+    for (locale, date) in [("C", "May 22"), ("en_US.UTF-8", "2024-05-22")] {
+        for args in [&["-H"][..], &["-H", "-u"], &["-H", "-T"], &["-H", "-uT"]] {
+            let result = ts
+                .ucmd()
+                .env("LC_ALL", locale)
+                .env("TZ", "UTC")
+                .args(args)
+                .arg("utmp")
+                .succeeds();
+            let mut lines = result.stdout_str().lines();
+            let (heading, line) = (lines.next().unwrap(), lines.next().unwrap());
+            let column = |line: &str, field: &str| line.find(field).unwrap();
+            assert_eq!(column(heading, "LINE"), column(line, "pts/1"), "{args:?}");
+            assert_eq!(column(heading, "TIME"), column(line, date), "{args:?}");
+            assert_eq!(
+                column(heading, "COMMENT"),
+                column(line, "(example.com)"),
+                "{args:?}"
+            );
+            if args.len() > 1 && args[1].contains('u') {
+                let pid_end = |line: &str, field: &str| column(line, field) + field.len();
+                assert_eq!(pid_end(heading, "PID"), pid_end(line, "4242"), "{args:?}");
+            }
+        }
+    }
+}
+
 #[cfg(unix)]
 #[test]
-#[ignore = "issue #3219"]
 fn test_short() {
     let ts = TestScenario::new(util_name!());
     for opt in ["-s", "--short", "--s"] {
@@ -70,6 +170,54 @@ fn test_short() {
     }
 }
 
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_short_overrides_columns() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write_bytes(
+        "utmp",
+        &utmp_record(
+            USER_PROCESS,
+            1234,
+            "pts/1",
+            "testusr",
+            "host",
+            1_716_371_283,
+        ),
+    );
+
+    // -u alone adds the idle time and pid, -s takes them away again.
+    ts.ucmd()
+        .args(&["-u", "-H", "utmp"])
+        .succeeds()
+        .stdout_matches(&regex::Regex::new(r"^NAME +LINE +TIME +IDLE +PID +COMMENT\n").unwrap());
+    ts.ucmd()
+        .args(&["-s", "-u", "-H", "utmp"])
+        .succeeds()
+        .stdout_matches(
+            &regex::Regex::new(r"^NAME +LINE +TIME +COMMENT\ntestusr +pts/1 +.* \(host\)\n$")
+                .unwrap(),
+        )
+        .stdout_does_not_contain("1234");
+
+    // Even with -d, whose exit status column stays
+    let mut records = utmp_record(8, 1234, "pts/2", "", "", 1_716_371_283);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        1235,
+        "pts/1",
+        "testusr",
+        "host",
+        1_716_371_283,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+    ts.ucmd()
+        .args(&["-s", "-d", "-H", "utmp"])
+        .succeeds()
+        .stdout_matches(&regex::Regex::new(r"^NAME +LINE +TIME +COMMENT +EXIT\n +pts/2 ").unwrap())
+        .stdout_does_not_contain("1234");
+}
+
 #[cfg(unix)]
 #[test]
 #[cfg(not(target_os = "openbsd"))]
@@ -128,7 +276,6 @@ fn test_time() {
 
 #[cfg(unix)]
 #[test]
-#[ignore = "issue #3219"]
 fn test_mesg() {
     // -T, -w, --mesg
     //     add user's message status as +, - or ?
@@ -151,6 +298,54 @@ fn test_mesg() {
     }
 }
 
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_mesg_states() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_716_371_201);
+    // Writable by the group, as /dev/null is writable by everyone
+    records.extend(utmp_record(
+        USER_PROCESS,
+        1,
+        "null",
+        "open",
+        "",
+        1_716_371_283,
+    ));
+    // Not writable by the group
+    records.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "../bin/sh",
+        "closed",
+        "",
+        1_716_371_283,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        3,
+        "pts/4242",
+        "gone",
+        "",
+        1_716_371_283,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+
+    for opt in ["-T", "-w", "--mesg", "--message", "--writable"] {
+        let result = ts.ucmd().args(&[opt, "utmp"]).succeeds();
+        let states: Vec<_> = result
+            .stdout_str()
+            .lines()
+            .map(|line| line.split_whitespace().take(2).collect::<Vec<_>>())
+            .collect();
+        assert_eq!(
+            states,
+            [["open", "+"], ["closed", "-"], ["gone", "?"]],
+            "{opt}"
+        );
+    }
+}
+
 #[cfg(unix)]
 #[test]
 #[cfg(not(target_os = "openbsd"))]
@@ -172,7 +367,6 @@ fn test_too_many_args() {
 
 #[cfg(unix)]
 #[test]
-#[ignore = "issue #3219"]
 fn test_users() {
     let ts = TestScenario::new(util_name!());
     for opt in ["-u", "--users", "--us"] {
@@ -181,17 +375,8 @@ fn test_users() {
         println!("actual: {actual:?}");
         println!("expect: {expect:?}");
 
-        let mut v_actual: Vec<&str> = actual.split_whitespace().collect();
-        let mut v_expect: Vec<&str> = expect.split_whitespace().collect();
-
-        // TODO: `--users` sometimes differs from GNU's output on macOS (race condition?)
-        // actual: "runner   console      Jun 23 06:37 00:34         196\n"
-        // expect: "runner   console      Jun 23 06:37  old          196\n"
-        if cfg!(target_os = "macos") {
-            v_actual.remove(5);
-            v_expect.remove(5);
-        }
-
+        let v_actual: Vec<&str> = actual.split_whitespace().collect();
+        let v_expect: Vec<&str> = expect.split_whitespace().collect();
         assert_eq!(v_actual, v_expect);
     }
 }
@@ -292,3 +477,1400 @@ fn test_piped_to_dev_full() {
         .fails()
         .stderr_is("who: No space left on device\n");
 }
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_command() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let mut child = std::process::Command::new("sleep")
+        .arg("30")
+        .spawn()
+        .unwrap();
+    at.write_bytes(
+        "utmp",
+        &utmp_record(
+            USER_PROCESS,
+            child.id() as i32,
+            "pts/42",
+            "testusr",
+            "",
+            1_716_371_283,
+        ),
+    );
+
+    let result = ucmd.args(&["--command", "utmp"]).run();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    result
+        .success()
+        .stdout_contains("testusr")
+        .stdout_contains(" sleep 30\n");
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_users_idle_before_boot_is_old() {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    // A boot record newer than the terminal's last access: any idle time
+    // measured from that access predates the boot and must be shown as "old".
+    let boot = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i32
+        + 3600;
+    let (at, mut ucmd) = at_and_ucmd!();
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", boot);
+    records.extend(utmp_record(USER_PROCESS, 1, "null", "testusr", "", boot));
+    at.write_bytes("utmp", &records);
+
+    ucmd.args(&["-u", "utmp"])
+        .succeeds()
+        .stdout_matches(&regex::Regex::new(r"testusr +null .* old +1\n").unwrap());
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_deduplicate() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let mut records = utmp_record(USER_PROCESS, 1, "pts/1", "testusr", "", 1_716_371_283);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/2",
+        "testusr",
+        "",
+        1_716_381_283,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        3,
+        "pts/3",
+        "other",
+        "",
+        1_716_371_283,
+    ));
+    at.write_bytes("utmp", &records);
+
+    let result = ucmd.args(&["--deduplicate", "utmp"]).succeeds();
+    let stdout = result.stdout_str();
+    assert_eq!(stdout.lines().count(), 2);
+    assert_eq!(stdout.matches("testusr").count(), 1);
+    assert!(stdout.contains("pts/2"));
+    assert!(!stdout.contains("pts/1"));
+    assert!(stdout.contains("other"));
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_without_deduplicate_shows_all_sessions() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let mut records = utmp_record(USER_PROCESS, 1, "pts/1", "testusr", "", 1_716_371_283);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/2",
+        "testusr",
+        "",
+        1_716_381_283,
+    ));
+    at.write_bytes("utmp", &records);
+
+    ucmd.arg("utmp")
+        .succeeds()
+        .stdout_contains("pts/1")
+        .stdout_contains("pts/2");
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_uptime_line() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_716_371_201);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        1,
+        "pts/1",
+        "testusr",
+        "",
+        1_716_371_283,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/2",
+        "testusr",
+        "",
+        1_716_381_283,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+    ts.fixtures.write_bytes(
+        "utmp1",
+        &utmp_record(USER_PROCESS, 1, "pts/1", "testusr", "", 1_716_371_283),
+    );
+
+    let re = regex::Regex::new(
+        r"^ \d\d:\d\d:\d\d  up  (\d+ days?,? )?\d\d:\d\d,  2 users,  load average: \d+\.\d\d, \d+\.\d\d, \d+\.\d\d\ntestusr ",
+    )
+    .unwrap();
+    ts.ucmd()
+        .args(&["--uptime", "utmp"])
+        .succeeds()
+        .stdout_matches(&re);
+    ts.ucmd()
+        .args(&["--uptime", "utmp1"])
+        .succeeds()
+        .stdout_contains(",  1 user,  ");
+    ts.ucmd()
+        .arg("utmp")
+        .succeeds()
+        .stdout_does_not_contain("load average");
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_numeric_user() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(USER_PROCESS, 1, "pts/1", "root", "", 1_716_371_283);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/2",
+        "testusr",
+        "",
+        1_716_381_283,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+
+    // Unknown users keep their name
+    ts.ucmd()
+        .args(&["--numeric-user", "utmp"])
+        .succeeds()
+        .stdout_matches(&regex::Regex::new(r"^0 +pts/1 .*\ntestusr +pts/2 ").unwrap());
+    ts.ucmd()
+        .arg("utmp")
+        .succeeds()
+        .stdout_matches(&regex::Regex::new(r"^root +pts/1 ").unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_version_full() {
+    let yes_no = |enabled: bool| if enabled { "yes" } else { "no" };
+    new_ucmd!()
+        .arg("--__version-full")
+        .succeeds()
+        .stdout_matches(
+            &regex::Regex::new(&format!(
+                "^version={}\n",
+                regex::escape(env!("CARGO_PKG_VERSION"))
+            ))
+            .unwrap(),
+        )
+        .stdout_contains(format!("target_os={}\n", std::env::consts::OS))
+        .stdout_contains("utmp=yes\n")
+        .stdout_contains(format!(
+            "systemd_logind={}\n",
+            yes_no(cfg!(feature = "feat_systemd_logind"))
+        ))
+        .stdout_contains(format!(
+            "proc={}\n",
+            yes_no(cfg!(any(target_os = "linux", target_os = "android")))
+        ));
+
+    new_ucmd!()
+        .arg("--help")
+        .succeeds()
+        .stdout_does_not_contain("__version-full");
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_users_idle_column() {
+    use filetime::{FileTime, set_file_atime};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let ts = TestScenario::new(util_name!());
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_500_000_000);
+    // The lines lead back out of /dev, where `who` looks them up.
+    for (tty, user, idle) in [
+        ("a", "active", 10),
+        ("b", "minutes", 5 * 60 + 10),
+        ("c", "hours", 13 * 3600 + 7 * 60),
+        ("d", "days", 2 * 86400),
+    ] {
+        ts.fixtures.touch(tty);
+        set_file_atime(
+            ts.fixtures.plus(tty),
+            FileTime::from_unix_time(now - idle, 0),
+        )
+        .unwrap();
+        let line = format!("..{}", ts.fixtures.plus_as_string(tty));
+        assert!(
+            line.len() <= 32,
+            "fixture path too long for ut_line: {line}"
+        );
+        records.extend(utmp_record(USER_PROCESS, 1, &line, user, "", 1_500_000_000));
+    }
+    records.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/4242",
+        "unknown",
+        "",
+        1_500_000_000,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+
+    let result = ts.ucmd().args(&["-u", "utmp"]).succeeds();
+    let idle: Vec<_> = result
+        .stdout_str()
+        .lines()
+        .map(|line| {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            (fields[0], fields[fields.len() - 2])
+        })
+        .collect();
+    assert_eq!(
+        idle,
+        [
+            ("active", "."),
+            ("minutes", "00:05"),
+            ("hours", "13:07"),
+            ("days", "old"),
+            ("unknown", "?"),
+        ]
+    );
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_min_idle() {
+    use filetime::{FileTime, set_file_atime};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let ts = TestScenario::new(util_name!());
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_500_000_000);
+    // Terminals idle for 10 minutes, 50 minutes and 2 hours. `who` looks
+    // them up under /dev, so the lines lead back out of it.
+    for (tty, user, idle) in [
+        ("a", "fresh", 600),
+        ("b", "almost", 3000),
+        ("c", "stale", 7200),
+    ] {
+        ts.fixtures.touch(tty);
+        set_file_atime(
+            ts.fixtures.plus(tty),
+            FileTime::from_unix_time(now - idle, 0),
+        )
+        .unwrap();
+        let line = format!("..{}", ts.fixtures.plus_as_string(tty));
+        assert!(
+            line.len() <= 32,
+            "fixture path too long for ut_line: {line}"
+        );
+        records.extend(utmp_record(USER_PROCESS, 1, &line, user, "", 1_500_000_000));
+    }
+    records.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/4242",
+        "unknown",
+        "",
+        1_500_000_000,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+
+    let users = |min_idle: &str| {
+        let result = ts.ucmd().args(&["--min-idle", min_idle, "utmp"]).succeeds();
+        result
+            .stdout_str()
+            .lines()
+            .map(|line| line.split_whitespace().next().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(users("0"), ["fresh", "almost", "stale"]);
+    assert_eq!(users("45m"), ["almost", "stale"]);
+    assert_eq!(users("1h"), ["stale"]);
+    assert!(users("3h").is_empty());
+
+    ts.ucmd()
+        .args(&["--min-idle", "soon", "utmp"])
+        .fails_with_code(1)
+        .stderr_contains("invalid minimum idle time: 'soon'");
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_tty() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_500_000_000);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        1,
+        "pts/1",
+        "first",
+        "",
+        1_500_000_000,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/2",
+        "second",
+        "",
+        1_500_000_000,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        3,
+        "pts/2",
+        "third",
+        "",
+        1_500_000_000,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+
+    let users = |tty: &str| {
+        let result = ts.ucmd().args(&["-a", "--tty", tty, "utmp"]).succeeds();
+        result
+            .stdout_str()
+            .lines()
+            .map(|line| line.split_whitespace().next().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(users("pts/1"), ["first"]);
+    assert_eq!(users("pts/2"), ["second", "third"]);
+    assert_eq!(users("/dev/pts/2"), ["second", "third"]);
+    assert!(users("pts/3").is_empty());
+    assert!(users("pts").is_empty());
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_file_rows() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_500_000_000);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        1,
+        "pts/1",
+        "alice",
+        "",
+        1_500_000_060,
+    ));
+    records.extend(utmp_record(8, 2, "pts/2", "", "", 1_500_000_120));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        3,
+        "pts/3",
+        "bob",
+        "example.org",
+        1_500_000_180,
+    ));
+    // Half of a record still being written
+    let partial = utmp_record(USER_PROCESS, 4, "pts/4", "carol", "", 1_500_000_240);
+    records.extend(&partial[..partial.len() / 2]);
+    ts.fixtures.write_bytes("wtmp", &records);
+
+    ts.ucmd().env("TZ", "UTC").arg("wtmp").succeeds().stdout_is(
+        "alice    pts/1        Jul 14 02:41\nbob      pts/3        Jul 14 02:43 (example.org)\n",
+    );
+    // A file that isn't there lists nothing, like GNU.
+    ts.ucmd().arg("missing").succeeds().no_output();
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_dead_from_file() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_500_000_000);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        1,
+        "pts/1",
+        "alice",
+        "",
+        1_500_000_060,
+    ));
+    // The logout on pts/2, with the exit status in ut_exit
+    let mut dead = utmp_record(8, 2, "pts/2", "", "", 1_500_000_120);
+    dead[332..334].copy_from_slice(&0i16.to_ne_bytes());
+    dead[334..336].copy_from_slice(&1i16.to_ne_bytes());
+    records.extend(dead);
+    ts.fixtures.write_bytes("wtmp", &records);
+
+    ts.ucmd()
+        .env("TZ", "UTC")
+        .args(&["-d", "wtmp"])
+        .succeeds()
+        .stdout_is("         pts/2        Jul 14 02:42                 2 id=2     term=0 exit=1\n");
+
+    // --all has the dead process among the rest, in the file's order.
+    let result = ts.ucmd().env("TZ", "UTC").args(&["-a", "wtmp"]).succeeds();
+    let lines: Vec<_> = result.stdout_str().lines().collect();
+    assert_eq!(lines.len(), 3, "{lines:?}");
+    assert!(lines[0].contains("system boot"), "{lines:?}");
+    assert!(lines[1].starts_with("alice"), "{lines:?}");
+    assert!(lines[2].ends_with("id=2     term=0 exit=1"), "{lines:?}");
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_count_only_user_processes() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_716_371_201);
+    ts.fixtures.write_bytes("empty", &records);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        1,
+        "pts/1",
+        "alice",
+        "",
+        1_716_371_283,
+    ));
+    // A dead process of a user who logged out
+    records.extend(utmp_record(8, 2, "pts/2", "carol", "", 1_716_371_283));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        3,
+        "pts/3",
+        "bob",
+        "h",
+        1_716_371_283,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+
+    for opt in ["-q", "--count"] {
+        ts.ucmd()
+            .args(&[opt, "empty"])
+            .succeeds()
+            .stdout_is("\n# users=0\n");
+        ts.ucmd()
+            .args(&[opt, "utmp"])
+            .succeeds()
+            .stdout_is("alice bob\n# users=2\n");
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_count_with_filters() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(USER_PROCESS, 1, "pts/1", "alice", "", 1_716_371_201);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/2",
+        "bob",
+        "",
+        1_716_371_283,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        3,
+        "pts/3",
+        "alice",
+        "",
+        1_716_371_283,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+
+    ts.ucmd()
+        .args(&["-q", "--deduplicate", "utmp"])
+        .succeeds()
+        .stdout_is("bob alice\n# users=2\n");
+    ts.ucmd()
+        .args(&["-q", "--tty=/dev/pts/3", "utmp"])
+        .succeeds()
+        .stdout_is("alice\n# users=1\n");
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_max_users() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_716_371_201);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        1,
+        "pts/1",
+        "alice",
+        "",
+        1_716_371_283,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/2",
+        "bob",
+        "",
+        1_716_371_283,
+    ));
+    // A second session of the same user
+    records.extend(utmp_record(
+        USER_PROCESS,
+        3,
+        "pts/3",
+        "alice",
+        "",
+        1_716_371_290,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+
+    for max in ["2", "3"] {
+        ts.ucmd()
+            .args(&["--max-users", max, "utmp"])
+            .succeeds()
+            .no_output();
+    }
+    for max in ["0", "1"] {
+        ts.ucmd()
+            .args(&["--max-users", max, "utmp"])
+            .fails_with_code(1)
+            .no_output();
+    }
+
+    ts.ucmd()
+        .args(&["--max-users", "many", "utmp"])
+        .fails_with_code(1)
+        .stderr_contains("invalid maximum number of users: 'many'");
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_count_by_host() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_716_371_201);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        1,
+        "pts/1",
+        "alice",
+        "gamma",
+        1_716_371_283,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/2",
+        "bob",
+        "beta",
+        1_716_371_283,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        3,
+        "tty2",
+        "carol",
+        "",
+        1_716_371_283,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        4,
+        "pts/3",
+        "alice",
+        "gamma",
+        1_716_371_283,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        5,
+        "pts/4",
+        "dave",
+        "beta:0",
+        1_716_371_283,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        6,
+        "pts/5",
+        "carol",
+        ":0",
+        1_716_371_283,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        7,
+        "pts/6",
+        "erin",
+        "alpha",
+        1_716_371_283,
+    ));
+    // Logged out, so not counted
+    records.extend(utmp_record(8, 8, "pts/7", "frank", "alpha", 1_716_371_283));
+    // IPv6 addresses are kept whole, as is a bracketed one without its display
+    for (pid, host) in [
+        (9, "2001:db8::1"),
+        (10, "2001:db8::1"),
+        (11, "::1"),
+        (12, "fe80::1%eth0"),
+        (13, "[2001:db8::2]:0"),
+    ] {
+        records.extend(utmp_record(
+            USER_PROCESS,
+            pid,
+            &format!("pts/{pid}"),
+            "grace",
+            host,
+            1_716_371_283,
+        ));
+    }
+    ts.fixtures.write_bytes("utmp", &records);
+
+    ts.ucmd()
+        .args(&["--count-by-host", "utmp"])
+        .succeeds()
+        .stdout_is(
+            "2001:db8::1: 2\nbeta: 2\ngamma: 2\nlocal: 2\n::1: 1\n[2001:db8::2]: 1\nalpha: 1\nfe80::1%eth0: 1\n",
+        );
+
+    // Only the sessions the filters keep are counted
+    ts.ucmd()
+        .args(&["--count-by-host", "--tty=pts/4", "utmp"])
+        .succeeds()
+        .stdout_is("beta: 1\n");
+    ts.ucmd()
+        .args(&["--count-by-host", "--deduplicate", "utmp"])
+        .succeeds()
+        .stdout_is("beta: 2\n2001:db8::1: 1\nalpha: 1\ngamma: 1\nlocal: 1\n");
+
+    ts.ucmd()
+        .args(&["--count-by-host", "-q", "utmp"])
+        .fails()
+        .stderr_contains("cannot be used with");
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_user_count_plurals() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_716_371_201);
+    for n in 0..=2 {
+        if n > 0 {
+            records.extend(utmp_record(
+                USER_PROCESS,
+                n,
+                &format!("pts/{n}"),
+                "testusr",
+                "",
+                1_716_371_283,
+            ));
+        }
+        let file = format!("utmp{n}");
+        ts.fixtures.write_bytes(&file, &records);
+
+        // The -q footer stays exactly as in GNU who
+        ts.ucmd()
+            .args(&["-q", &file])
+            .succeeds()
+            .stdout_contains(format!("\n# users={n}\n"));
+
+        let summary = if n == 1 {
+            ",  1 user,  ".to_string()
+        } else {
+            format!(",  {n} users,  ")
+        };
+        ts.ucmd()
+            .args(&["--uptime", &file])
+            .succeeds()
+            .stdout_contains(summary);
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_sort() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_716_371_201);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        1,
+        "pts/1",
+        "carol",
+        "",
+        1_716_381_283,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/2",
+        "alice",
+        "",
+        1_716_391_283,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        3,
+        "pts/3",
+        "bob",
+        "",
+        1_716_371_283,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+
+    let users = |args: &[&str]| -> Vec<String> {
+        ts.ucmd()
+            .args(args)
+            .arg("utmp")
+            .succeeds()
+            .stdout_str()
+            .lines()
+            .map(|line| line.split_whitespace().next().unwrap().to_owned())
+            .collect()
+    };
+
+    assert_eq!(users(&[]), ["carol", "alice", "bob"]);
+    assert_eq!(users(&["--sort=user"]), ["alice", "bob", "carol"]);
+    assert_eq!(users(&["--sort=time"]), ["bob", "carol", "alice"]);
+    assert_eq!(
+        users(&["--sort=time", "--reverse"]),
+        ["alice", "carol", "bob"]
+    );
+    // The boot record keeps its place
+    assert_eq!(
+        users(&["-b", "-u", "--sort=user", "--reverse"])[1..],
+        ["carol", "bob", "alice"]
+    );
+
+    ts.ucmd().args(&["--reverse", "utmp"]).fails();
+}
+
+#[test]
+fn test_watch_interval_invalid() {
+    for interval in ["0", "0s", "-1", "abc"] {
+        new_ucmd!()
+            .arg(format!("--watch-interval={interval}"))
+            .fails_with_code(1)
+            .stderr_contains("invalid watch interval");
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_watch_interval() {
+    let ts = TestScenario::new(util_name!());
+    let first = utmp_record(USER_PROCESS, 1, "pts/1", "testusr", "", 1_716_371_283);
+    ts.fixtures.write_bytes("utmp", &first);
+
+    let mut child = ts
+        .ucmd()
+        .args(&["--watch-interval=0.1", "utmp"])
+        .run_no_wait();
+    child.delay(500);
+
+    let mut records = first.clone();
+    records.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/9",
+        "testusr",
+        "",
+        1_716_381_283,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+
+    // Printed once at startup and once more after the change, but not on
+    // the polls in between
+    child
+        .delay(500)
+        .kill()
+        .make_assertion()
+        .with_current_output()
+        .stdout_matches(
+            &regex::Regex::new(r"^testusr +pts/1 .*\n\ntestusr +pts/1 .*\ntestusr +pts/9 .*\n$")
+                .unwrap(),
+        );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_double_dash_file() {
+    let ts = TestScenario::new(util_name!());
+    let expected = ts.ucmd().arg("/var/log/wtmp").run().stdout_move_str();
+    ts.ucmd()
+        .args(&["--", "/var/log/wtmp"])
+        .succeeds()
+        .stdout_is(expected);
+
+    let expected = ts.ucmd().args(&["am", "i"]).run().stdout_move_str();
+    ts.ucmd()
+        .args(&["--", "am", "i"])
+        .succeeds()
+        .stdout_is(expected);
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_double_dash_option_like_file() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write_bytes(
+        "-q",
+        &utmp_record(USER_PROCESS, 1, "pts/1", "testusr", "", 1_716_371_283),
+    );
+
+    ucmd.args(&["--", "-q"])
+        .succeeds()
+        .stdout_matches(&regex::Regex::new(r"^testusr +pts/1 ").unwrap());
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_output_json() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_716_370_000);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        42,
+        "pts/4242",
+        "test\"usr",
+        "example.com",
+        1_716_371_283,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+
+    ts.ucmd()
+        .env("TZ", "UTC")
+        .args(&["--output=json", "utmp"])
+        .succeeds()
+        .stdout_is(concat!(
+            "[\n",
+            r#"  {"type":"user","user":"test\"usr","line":"pts/4242","host":"example.com","login_time":"2024-05-22T09:48:03Z"}"#,
+            "\n]\n",
+        ));
+
+    // Cosmetic options don't change it
+    ts.ucmd()
+        .env("TZ", "UTC")
+        .args(&["--output=json", "-b", "-H", "-T", "--terse", "utmp"])
+        .succeeds()
+        .stdout_is(concat!(
+            "[\n",
+            r#"  {"type":"boot","user":"reboot","line":"~","host":"","login_time":"2024-05-22T09:26:40Z","pid":0}"#,
+            "\n]\n",
+        ));
+
+    ts.ucmd()
+        .env("TZ", "UTC")
+        .args(&["--output=json", "-b", "-u", "utmp"])
+        .succeeds()
+        .stdout_matches(
+            &regex::Regex::new(
+                r#"^\[\n  \{"type":"boot",[^\n]*\},\n  \{"type":"user",[^\n]*\}\n\]\n$"#,
+            )
+            .unwrap(),
+        );
+
+    ts.ucmd()
+        .args(&["--output=json", "-r", "utmp"])
+        .succeeds()
+        .stdout_is("[]\n");
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_output_json_fields() {
+    let ts = TestScenario::new(util_name!());
+    let records = utmp_record(
+        USER_PROCESS,
+        42,
+        "pts/4242",
+        "testusr",
+        "example.com",
+        1_500_000_000,
+    );
+    ts.fixtures.write_bytes("utmp", &records);
+
+    let json = |args: &[&str]| {
+        ts.ucmd()
+            .env("TZ", "UTC")
+            .arg("--output=json")
+            .args(args)
+            .arg("utmp")
+            .succeeds()
+            .stdout_move_str()
+    };
+
+    // The fields follow the columns of the text output, with -s keeping
+    // only the user, line and time
+    let short = concat!(
+        "[\n",
+        r#"  {"user":"testusr","line":"pts/4242","login_time":"2017-07-14T02:40:00Z"}"#,
+        "\n]\n",
+    );
+    for args in [&["-s"][..], &["-s", "-T"], &["-s", "-u"]] {
+        assert_eq!(json(args), short);
+    }
+    assert_eq!(
+        json(&[]),
+        concat!(
+            "[\n",
+            r#"  {"type":"user","user":"testusr","line":"pts/4242","host":"example.com","login_time":"2017-07-14T02:40:00Z"}"#,
+            "\n]\n",
+        )
+    );
+    let users = concat!(
+        "[\n",
+        r#"  {"type":"user","user":"testusr","line":"pts/4242","host":"example.com","login_time":"2017-07-14T02:40:00Z","pid":42,"idle":null}"#,
+        "\n]\n",
+    );
+    for args in [&["-u"][..], &["-u", "-H"]] {
+        assert_eq!(json(args), users);
+    }
+    assert_ne!(json(&["-s"]), json(&["-u"]));
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_output_wtmp_round_trip() {
+    let ts = TestScenario::new(util_name!());
+    let mut records = utmp_record(BOOT_TIME, 0, "~", "reboot", "", 1_716_371_201);
+    records.extend(utmp_record(
+        USER_PROCESS,
+        1,
+        "pts/1",
+        "testusr",
+        "example.org",
+        1_716_371_283,
+    ));
+    records.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/2",
+        "testusr",
+        "",
+        1_716_381_283,
+    ));
+    ts.fixtures.write_bytes("utmp", &records);
+
+    // Only the selected records are written, unchanged
+    let output = ts
+        .ucmd()
+        .args(&["--output=wtmp", "--deduplicate", "utmp"])
+        .succeeds()
+        .stdout_move_bytes();
+    assert_eq!(output, records[2 * 384..]);
+    ts.fixtures.write_bytes("filtered", &output);
+    ts.ucmd()
+        .arg("filtered")
+        .succeeds()
+        .stdout_matches(&regex::Regex::new(r"^testusr +pts/2 [^\n]*\n$").unwrap());
+
+    // Everything that is shown makes it through
+    let output = ts
+        .ucmd()
+        .args(&["--output=wtmp", "-b", "-u", "utmp"])
+        .succeeds()
+        .stdout_move_bytes();
+    assert_eq!(output, records);
+    ts.fixtures.write_bytes("all", &output);
+    let expected = ts
+        .ucmd()
+        .args(&["-b", "-u", "utmp"])
+        .succeeds()
+        .stdout_move_str();
+    ts.ucmd()
+        .args(&["-b", "-u", "all"])
+        .succeeds()
+        .stdout_is(expected);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unreadable_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // Permissions don't apply to root
+    if rustix::process::geteuid().is_root() {
+        return;
+    }
+
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("utmp", "");
+    let path = at.plus("utmp");
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o000)).unwrap();
+
+    ucmd.arg("utmp")
+        .fails_with_code(1)
+        .no_stdout()
+        .stderr_is("who: utmp: Permission denied\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_missing_file() {
+    new_ucmd!().arg("does-not-exist").succeeds().no_output();
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_header_format() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write_bytes(
+        "utmp",
+        &utmp_record(USER_PROCESS, 1, "pts/1", "testusr", "", 1_716_371_283),
+    );
+
+    ucmd.args(&["-H", "--header-format=name=USER,line=TTY", "utmp"])
+        .succeeds()
+        .stdout_matches(&regex::Regex::new(r"^USER +TTY +TIME +COMMENT\ntestusr +pts/1 ").unwrap());
+}
+
+#[test]
+fn test_header_format_invalid() {
+    for labels in ["nmae=USER", "USER", "name=USER,"] {
+        new_ucmd!()
+            .args(&["-H", "--header-format", labels])
+            .fails_with_code(1)
+            .stderr_contains("invalid column label");
+    }
+}
+
+#[test]
+fn test_resolve_timeout_invalid() {
+    for timeout in ["0", "soon"] {
+        new_ucmd!()
+            .args(&["--lookup", "--resolve-timeout", timeout])
+            .fails_with_code(1)
+            .stderr_contains("invalid resolve timeout");
+    }
+}
+
+#[cfg(not(feature = "feat_systemd_logind"))]
+#[test]
+fn test_seats_needs_logind() {
+    new_ucmd!()
+        .arg("--seats")
+        .fails_with_code(1)
+        .stderr_contains("--seats needs systemd-logind support");
+}
+
+#[test]
+fn test_host_width_invalid() {
+    for width in ["1.5", "wide"] {
+        new_ucmd!()
+            .args(&["--host-width", width])
+            .fails_with_code(1)
+            .stderr_contains("invalid host width");
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_best_effort_keeps_healthy_output() {
+    let ts = TestScenario::new(util_name!());
+    let mut utmp = utmp_record(USER_PROCESS, 1, "pts/1", "testusr", ":0", 1_716_371_283);
+    utmp.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/2",
+        "otherusr",
+        "",
+        1_716_371_283,
+    ));
+    ts.fixtures.write_bytes("utmp", &utmp);
+
+    let expected = ts
+        .ucmd()
+        .args(&["--lookup", "utmp"])
+        .succeeds()
+        .stdout_move_str();
+    ts.ucmd()
+        .args(&["--lookup", "--best-effort", "utmp"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(expected);
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_host_width() {
+    let ts = TestScenario::new(util_name!());
+    let host = "h".repeat(250);
+    ts.fixtures.write_bytes(
+        "utmp",
+        &utmp_record(USER_PROCESS, 1, "pts/1", "testusr", &host, 1_716_371_283),
+    );
+
+    ts.ucmd()
+        .args(&["--host-width", "20", "utmp"])
+        .succeeds()
+        .stdout_contains(format!("({}...)\n", "h".repeat(17)));
+    for width in ["250", "0"] {
+        ts.ucmd()
+            .args(&["--host-width", width, "utmp"])
+            .succeeds()
+            .stdout_contains(format!("({host})\n"));
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_display_host() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write_bytes(
+        "utmp",
+        &utmp_record(USER_PROCESS, 1, "tty7", "testusr", ":0", 1_716_371_283),
+    );
+
+    let display_host = regex::Regex::new(r"^testusr +tty7 +.* \(:0\)\n$").unwrap();
+    for args in [&["utmp"][..], &["--lookup", "utmp"]] {
+        ts.ucmd()
+            .args(args)
+            .succeeds()
+            .stdout_matches(&display_host);
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_config_file() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.write_bytes(
+        "utmp",
+        &utmp_record(USER_PROCESS, 1, "pts/1", "testusr", "", 1_716_371_283),
+    );
+    at.mkdir_all("config/uutils");
+    at.write(
+        "config/uutils/who.toml",
+        "# who defaults\n\nheader-format = \"name=USER\"\nidle-threshold = \"1h\"\n",
+    );
+    let config_home = at.plus_as_string("config");
+
+    ts.ucmd()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .args(&["-H", "utmp"])
+        .succeeds()
+        .no_stderr()
+        .stdout_matches(&regex::Regex::new(r"^USER +LINE ").unwrap());
+
+    // The command line takes precedence
+    ts.ucmd()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .args(&["-H", "--header-format=name=WHO", "utmp"])
+        .succeeds()
+        .stdout_matches(&regex::Regex::new(r"^WHO +LINE ").unwrap());
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_config_file_output() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.write_bytes(
+        "utmp",
+        &utmp_record(USER_PROCESS, 1, "pts/1", "testusr", "", 1_716_371_283),
+    );
+    at.mkdir_all("config/uutils");
+    at.write("config/uutils/who.toml", "output = \"json\"\n");
+    let config_home = at.plus_as_string("config");
+
+    ts.ucmd()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .arg("utmp")
+        .succeeds()
+        .no_stderr()
+        .stdout_contains("\"user\":\"testusr\"")
+        .stdout_matches(&regex::Regex::new(r"^\[\n  \{").unwrap());
+
+    // Options that --output conflicts with leave the configured format out
+    ts.ucmd()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .args(&["-q", "utmp"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is("testusr\n# users=1\n");
+
+    // An unknown format is warned about and ignored, like other values
+    at.write("config/uutils/who.toml", "output = \"yaml\"\n");
+    ts.ucmd()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .arg("utmp")
+        .succeeds()
+        .stderr_contains("invalid output format: 'yaml'")
+        .stdout_matches(&regex::Regex::new(r"^testusr +pts/1 ").unwrap());
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_config_file_malformed() {
+    let ts = TestScenario::new(util_name!());
+    let at = &ts.fixtures;
+    at.write_bytes(
+        "utmp",
+        &utmp_record(USER_PROCESS, 1, "pts/1", "testusr", "", 1_716_371_283),
+    );
+    at.mkdir_all("config/uutils");
+    at.write(
+        "config/uutils/who.toml",
+        "color = \"always\"\nidle-threshold = 1h\nheader-format = \"nope=X\"\n",
+    );
+
+    ts.ucmd()
+        .env("XDG_CONFIG_HOME", at.plus_as_string("config"))
+        .args(&["-H", "utmp"])
+        .succeeds()
+        .stderr_contains("line 1: expected a known option")
+        .stderr_contains("line 2: expected a known option")
+        .stderr_contains("invalid column label")
+        .stdout_matches(&regex::Regex::new(r"^NAME +LINE ").unwrap());
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_terse() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write_bytes(
+        "utmp",
+        &utmp_record(USER_PROCESS, 1, "pts/1", "testusr", "host", 1_716_371_283),
+    );
+
+    ucmd.args(&["--terse", "-H", "utmp"])
+        .succeeds()
+        .stdout_matches(
+            &regex::Regex::new(r"^NAME LINE TIME COMMENT\ntestusr pts/1 (\S+ )+\(host\)\n$")
+                .unwrap(),
+        );
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_time_style() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write_bytes(
+        "utmp",
+        &utmp_record(USER_PROCESS, 1, "pts/1", "testusr", "", 1_500_000_000),
+    );
+
+    for (style, time) in [
+        ("iso", "2017-07-14"),
+        ("long-iso", "2017-07-14 02:40"),
+        ("+%Y%m%dT%H%M%S", "20170714T024000"),
+    ] {
+        ts.ucmd()
+            .env("TZ", "UTC")
+            .args(&["-H", &format!("--time-style={style}"), "utmp"])
+            .succeeds()
+            .stdout_is(format!(
+                "{:<8} {:<12} {:<w$} COMMENT\ntestusr  pts/1        {time}\n",
+                "NAME",
+                "LINE",
+                "TIME",
+                w = time.len()
+            ));
+    }
+
+    ts.ucmd()
+        .args(&["--time-style=locale", "utmp"])
+        .fails_with_code(1)
+        .stderr_contains("invalid time style: 'locale'");
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_long_user_name_aligned() {
+    let ts = TestScenario::new(util_name!());
+    let long_name = "a".repeat(30);
+    let mut utmp = utmp_record(USER_PROCESS, 1, "pts/1", &long_name, "", 1_716_371_283);
+    utmp.extend(utmp_record(
+        USER_PROCESS,
+        2,
+        "pts/2",
+        "testusr",
+        "",
+        1_716_371_283,
+    ));
+    ts.fixtures.write_bytes("utmp", &utmp);
+
+    let stdout = ts.ucmd().args(&["-H", "utmp"]).succeeds().stdout_move_str();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3);
+    for line in &lines {
+        assert_eq!(line.find(['L', 'p']), Some(31), "{line:?}");
+    }
+    assert!(lines[1].starts_with(&format!("{long_name} pts/1 ")));
+
+    // Short names keep GNU's widths, and --no-pad drops the padding.
+    ts.ucmd()
+        .args(&["--no-pad", "utmp"])
+        .succeeds()
+        .stdout_matches(&regex::Regex::new(r"\ntestusr pts/2 \S").unwrap());
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_header_repeat() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let utmp: Vec<u8> = (0..5)
+        .flat_map(|i| {
+            utmp_record(
+                USER_PROCESS,
+                i + 1,
+                &format!("pts/{i}"),
+                &format!("user{i}"),
+                "",
+                1_716_371_283,
+            )
+        })
+        .collect();
+    at.write_bytes("utmp", &utmp);
+
+    let stdout = ucmd
+        .args(&["--terse", "--header-repeat", "2", "utmp"])
+        .succeeds()
+        .stdout_move_str();
+    let headings: Vec<_> = stdout
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.starts_with("NAME "))
+        .map(|(i, _)| i)
+        .collect();
+    assert_eq!(headings, [0, 3, 6]);
+    assert_eq!(stdout.lines().count(), 8);
+}
+
+#[test]
+fn test_header_repeat_invalid() {
+    for count in ["-1", "often"] {
+        new_ucmd!()
+            .arg(format!("--header-repeat={count}"))
+            .fails_with_code(1)
+            .stderr_contains("invalid header repeat count");
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_many_records() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let utmp: Vec<u8> = (0..500)
+        .flat_map(|i| {
+            utmp_record(
+                USER_PROCESS,
+                i + 1,
+                &format!("pts/{}", i % 10),
+                &format!("user{i}"),
+                "",
+                1_716_371_283,
+            )
+        })
+        .collect();
+    at.write_bytes("utmp", &utmp);
+
+    let stdout = ucmd.arg("utmp").succeeds().stdout_move_str();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 500);
+    for (i, line) in lines.iter().enumerate() {
+        assert!(line.starts_with(&format!("{:<8} pts/{} ", format!("user{i}"), i % 10)));
+    }
+}
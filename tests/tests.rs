@@ -202,6 +202,10 @@ mod test_join;
 #[path = "by-util/test_kill.rs"]
 mod test_kill;
 
+#[cfg(feature = "last")]
+#[path = "by-util/test_last.rs"]
+mod test_last;
+
 #[cfg(feature = "link")]
 #[path = "by-util/test_link.rs"]
 mod test_link;
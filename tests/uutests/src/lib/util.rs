@@ -2957,6 +2957,41 @@ pub fn pty_path() -> (String, File, File) {
     (path, pty.master.into(), pty.slave.into())
 }
 
+/// Serialize a single glibc `struct utmpx` record, so tests can point
+/// utilities such as `who`, `users` or `last` at a crafted utmp or wtmp file
+/// instead of the live database.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+pub fn utmp_record(
+    ut_type: i16,
+    pid: i32,
+    line: &str,
+    user: &str,
+    host: &str,
+    tv_sec: i32,
+) -> Vec<u8> {
+    fn push_str(buf: &mut Vec<u8>, s: &str, len: usize) {
+        let mut field = vec![0u8; len];
+        field[..s.len()].copy_from_slice(s.as_bytes());
+        buf.extend_from_slice(&field);
+    }
+
+    let mut buf = Vec::with_capacity(384);
+    buf.extend_from_slice(&ut_type.to_ne_bytes());
+    buf.extend_from_slice(&[0; 2]);
+    buf.extend_from_slice(&pid.to_ne_bytes());
+    push_str(&mut buf, line, 32);
+    push_str(&mut buf, line.rsplit('/').next().unwrap_or_default(), 4);
+    push_str(&mut buf, user, 32);
+    push_str(&mut buf, host, 256);
+    // ut_exit, ut_session
+    buf.extend_from_slice(&[0; 8]);
+    buf.extend_from_slice(&tv_sec.to_ne_bytes());
+    buf.extend_from_slice(&0i32.to_ne_bytes());
+    // ut_addr_v6, __glibc_reserved
+    buf.extend_from_slice(&[0; 36]);
+    buf
+}
+
 /// Add prefix 'g' for `util_name` if not on linux
 #[cfg(unix)]
 pub fn host_name_for(util_name: &str) -> Cow<'_, str> {